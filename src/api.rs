@@ -0,0 +1,585 @@
+use std::{
+    env,
+    fmt,
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+use poise::serenity_prelude::{
+    self,
+    futures::future::BoxFuture,
+    ChannelId,
+    CreateMessage,
+    GuildId,
+    UserId,
+};
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{json, Value};
+use tokio::{
+    io::BufReader,
+    net::{TcpListener, TcpStream},
+};
+
+use crate::{
+    commands::{
+        question::{collect_judgement, draw_question, get_judge_buttons, get_question_embed},
+        set_lives::get_lives_changed_embeds,
+        vote::{
+            begin_voting,
+            drive_voting,
+            follow_up_resolution,
+            get_resolution_embeds,
+            send_voting_message,
+        },
+        GameChannel,
+    },
+    data::Data,
+    engine::{Command, EngineError},
+    http::{self, Request},
+    i18n::{localize_error, Locale, Localize},
+    overlay::OverlayState,
+    t,
+    CmdRet,
+};
+
+// the same limits as `/start-voting`
+const MIN_VOTING_DURATION: u32 = 10;
+const MAX_VOTING_DURATION: u32 = 3600;
+
+#[non_exhaustive]
+#[derive(Debug, thiserror::Error)]
+pub enum ApiError {
+    MissingSetting(&'static str),
+    InvalidSetting(&'static str, String),
+    Unauthorized,
+    NotFound,
+    NoActiveGame,
+    // the reason is already in the language of the guild
+    BadRequest(String),
+    Engine(#[from] EngineError),
+    Conflict(String),
+}
+
+impl Localize for ApiError {
+    fn localize(&self, locale: Locale) -> String {
+        match self {
+            ApiError::MissingSetting(setting) => {
+                t!(locale, "errors.api.missing_setting", setting = setting)
+            },
+            ApiError::InvalidSetting(setting, value) => t!(
+                locale,
+                "errors.api.invalid_setting",
+                setting = setting,
+                value = value
+            ),
+            ApiError::Unauthorized => t!(locale, "errors.api.unauthorized"),
+            ApiError::NotFound => t!(locale, "errors.api.not_found"),
+            ApiError::NoActiveGame => t!(locale, "checks.no_active_game"),
+            ApiError::BadRequest(reason) => {
+                t!(locale, "errors.api.bad_request", reason = reason)
+            },
+            ApiError::Engine(err) => err.localize(locale),
+            ApiError::Conflict(reason) => reason.clone(),
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.localize(Locale::default()))
+    }
+}
+
+impl ApiError {
+    pub fn status(&self) -> &'static str {
+        match self {
+            ApiError::Unauthorized => "401 Unauthorized",
+            ApiError::NotFound | ApiError::NoActiveGame => "404 Not Found",
+            ApiError::BadRequest(_) => "400 Bad Request",
+            ApiError::Engine(_) | ApiError::Conflict(_) => "409 Conflict",
+            ApiError::MissingSetting(_) | ApiError::InvalidSetting(..) => {
+                "500 Internal Server Error"
+            },
+        }
+    }
+}
+
+/// The settings of the control API, read from the same environment as `TOKEN`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiConfig {
+    pub address: SocketAddr,
+    // sent as `Authorization: Bearer <token>`
+    pub token: String,
+}
+
+impl ApiConfig {
+    /// Returns `None` if `API_ADDRESS` isn't set, the API doesn't run then
+    pub fn from_env() -> Result<Option<Self>, ApiError> {
+        let Ok(address) = env::var("API_ADDRESS") else {
+            return Ok(None);
+        };
+        let token = env::var("API_TOKEN")
+            .ok()
+            .filter(|token| !token.is_empty())
+            .ok_or(ApiError::MissingSetting("API_TOKEN"))?;
+
+        Ok(Some(Self {
+            address: address
+                .parse()
+                .map_err(|_| ApiError::InvalidSetting("API_ADDRESS", address))?,
+            token,
+        }))
+    }
+}
+
+/// What a request asks for, every route is below `/api/guilds/<guild ID>/`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Route {
+    /// `GET game`
+    ShowGame,
+    /// `POST voting/start`
+    StartVoting,
+    /// `POST voting/end`
+    EndVoting,
+    /// `POST lives`
+    SetLives,
+    /// `POST question`
+    AskQuestion,
+}
+
+impl Route {
+    pub fn parse(method: &str, path: &str) -> Option<(GuildId, Self)> {
+        let rest = path.strip_prefix("/api/guilds/")?;
+        let (guild_id, action) = rest.split_once('/')?;
+        let guild_id = guild_id.parse().ok()?;
+
+        let route = match (method, action.trim_end_matches('/')) {
+            ("GET", "game") => Route::ShowGame,
+            ("POST", "voting/start") => Route::StartVoting,
+            ("POST", "voting/end") => Route::EndVoting,
+            ("POST", "lives") => Route::SetLives,
+            ("POST", "question") => Route::AskQuestion,
+            _ => return None,
+        };
+
+        Some((guild_id, route))
+    }
+}
+
+pub fn is_authorized(request: &Request, token: &str) -> bool {
+    request
+        .header("authorization")
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|given| constant_time_eq(given.as_bytes(), token.as_bytes()))
+}
+
+/// Looks at every byte, so the response time doesn't tell how much of a guessed token was right
+fn constant_time_eq(given: &[u8], expected: &[u8]) -> bool {
+    given.len() == expected.len()
+        && given
+            .iter()
+            .zip(expected)
+            .fold(0, |difference, (given, expected)| {
+                difference | (given ^ expected)
+            })
+            == 0
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct StartVotingRequest {
+    // in seconds, no timer if it's missing
+    pub duration: Option<u32>,
+    pub anonymous: bool,
+    // replaces a running voting instead of failing
+    #[serde(rename = "override")]
+    pub override_running: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetLivesRequest {
+    pub player: UserId,
+    // either the new amount of lives or how many are added (or taken with a negative number)
+    pub lives: Option<i32>,
+    pub change: Option<i32>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct AskQuestionRequest {
+    // the finalist on turn during the finale
+    pub player: Option<UserId>,
+    pub category: Option<String>,
+}
+
+/// An empty body is read as `{}`
+pub fn parse_body<T: DeserializeOwned>(body: &[u8]) -> Result<T, ApiError> {
+    let body = if body.iter().all(u8::is_ascii_whitespace) {
+        b"{}"
+    } else {
+        body
+    };
+
+    serde_json::from_slice(body).map_err(|error| ApiError::BadRequest(error.to_string()))
+}
+
+/// Lets a Stream Deck or a hotkey tool run the moderator's commands. The game is changed the same
+/// way the commands do it, and the messages show up in the channel of the game.
+pub struct ControlApi {
+    serenity: serenity_prelude::Context,
+    data: Arc<Data>,
+    token: String,
+    // small numbers never clash with the interaction IDs the commands use for their buttons
+    next_id: AtomicU64,
+}
+
+impl ControlApi {
+    pub fn new(serenity: serenity_prelude::Context, data: Arc<Data>, token: String) -> Self {
+        Self {
+            serenity,
+            data,
+            token,
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    /// Errors are answered in the language of the guild, if the request is allowed to know it
+    pub async fn locale(&self, request: &Request) -> Locale {
+        if !is_authorized(request, &self.token) {
+            return Locale::default();
+        }
+
+        match Route::parse(&request.method, &request.path) {
+            Some((guild_id, _)) => self.data.session(guild_id).await.locale().await,
+            None => Locale::default(),
+        }
+    }
+
+    pub async fn handle(&self, request: &Request) -> Result<Value, ApiError> {
+        if !is_authorized(request, &self.token) {
+            return Err(ApiError::Unauthorized);
+        }
+        let (guild_id, route) =
+            Route::parse(&request.method, &request.path).ok_or(ApiError::NotFound)?;
+        tracing::info!(%guild_id, ?route, "control API request");
+
+        match route {
+            Route::ShowGame => self.show_game(guild_id).await,
+            Route::StartVoting => {
+                self.start_voting(guild_id, parse_body(&request.body)?)
+                    .await
+            },
+            Route::EndVoting => self.end_voting(guild_id).await,
+            Route::SetLives => self.set_lives(guild_id, parse_body(&request.body)?).await,
+            Route::AskQuestion => {
+                self.ask_question(guild_id, parse_body(&request.body)?)
+                    .await
+            },
+        }
+    }
+
+    async fn show_game(&self, guild_id: GuildId) -> Result<Value, ApiError> {
+        let mut state = {
+            let session = self.data.session(guild_id).await;
+            let locale = session.locale().await;
+            let lock = session.engine.lock().await;
+            OverlayState::new(lock.as_ref().ok_or(ApiError::NoActiveGame)?, locale)
+        };
+        self.data.overlay.fill_names(guild_id, &mut state).await;
+
+        Ok(json!(state))
+    }
+
+    async fn start_voting(
+        &self,
+        guild_id: GuildId,
+        request: StartVotingRequest,
+    ) -> Result<Value, ApiError> {
+        let session = self.data.session(guild_id).await;
+        let locale = session.locale().await;
+
+        if request.duration.is_some_and(|duration| {
+            !(MIN_VOTING_DURATION..=MAX_VOTING_DURATION).contains(&duration)
+        }) {
+            return Err(ApiError::BadRequest(t!(
+                locale,
+                "errors.api.duration",
+                min = MIN_VOTING_DURATION,
+                max = MAX_VOTING_DURATION
+            )));
+        }
+
+        let (channel_id, voting_id, deadline, summary) = {
+            let mut lock = session.engine.lock().await;
+            let engine = lock.as_mut().ok_or(ApiError::NoActiveGame)?;
+
+            if engine.voting().is_some() && !request.override_running {
+                return Err(EngineError::VotingAlreadyActive.into());
+            }
+
+            let moderator = engine.game().moderator;
+            let (voting_id, summary) = begin_voting(
+                engine,
+                moderator,
+                request.duration,
+                request.anonymous,
+                locale,
+            )?;
+            let deadline = engine.voting().and_then(|voting| voting.deadline);
+
+            (engine.game().channel_id, voting_id, deadline, summary)
+        };
+        self.data.persist_or_log().await;
+
+        self.spawn_in_channel(guild_id, channel_id, move |channel| {
+            Box::pin(async move {
+                if let Some(summary) = summary {
+                    channel
+                        .channel_id
+                        .send_message(channel.serenity, CreateMessage::new().embed(summary))
+                        .await?;
+                }
+
+                let message = send_voting_message(channel, voting_id).await?;
+                drive_voting(channel, message, voting_id).await
+            })
+        });
+
+        Ok(json!({ "voting_id": voting_id, "deadline": deadline }))
+    }
+
+    async fn end_voting(&self, guild_id: GuildId) -> Result<Value, ApiError> {
+        let session = self.data.session(guild_id).await;
+        let locale = session.locale().await;
+        let (channel_id, events) = {
+            let mut lock = session.engine.lock().await;
+            let engine = lock.as_mut().ok_or(ApiError::NoActiveGame)?;
+
+            let events = engine.handle(Command::EndVoting)?;
+            session.record_finished_game(engine, &events).await;
+            (engine.game().channel_id, events)
+        };
+        self.data.persist_or_log().await;
+
+        let response = json!({ "events": events });
+        self.spawn_in_channel(guild_id, channel_id, move |channel| {
+            Box::pin(async move {
+                channel
+                    .channel_id
+                    .send_message(
+                        channel.serenity,
+                        CreateMessage::new().embeds(get_resolution_embeds(&events, locale)),
+                    )
+                    .await?;
+
+                if let Some((message, voting_id)) = follow_up_resolution(channel, &events).await? {
+                    drive_voting(channel, message, voting_id).await?;
+                }
+                Ok(())
+            })
+        });
+
+        Ok(response)
+    }
+
+    async fn set_lives(
+        &self,
+        guild_id: GuildId,
+        request: SetLivesRequest,
+    ) -> Result<Value, ApiError> {
+        let session = self.data.session(guild_id).await;
+        let locale = session.locale().await;
+        let (channel_id, lives, events) = {
+            let mut lock = session.engine.lock().await;
+            let engine = lock.as_mut().ok_or(ApiError::NoActiveGame)?;
+
+            let lives = match (request.lives, request.change) {
+                (Some(lives), None) => lives,
+                (None, Some(change)) => {
+                    let current = engine
+                        .game()
+                        .members
+                        .get(&request.player)
+                        .copied()
+                        .unwrap_or_default();
+                    current + change
+                },
+                _ => {
+                    return Err(ApiError::BadRequest(t!(
+                        locale,
+                        "errors.api.lives_or_change"
+                    )))
+                },
+            };
+
+            let events = engine.handle(Command::SetLives {
+                player: request.player,
+                lives,
+            })?;
+            session.record_finished_game(engine, &events).await;
+            (engine.game().channel_id, lives, events)
+        };
+        self.data.persist_or_log().await;
+
+        let response = json!({ "player": request.player, "lives": lives, "events": events });
+        self.spawn_in_channel(guild_id, channel_id, move |channel| {
+            Box::pin(async move {
+                channel
+                    .channel_id
+                    .send_message(
+                        channel.serenity,
+                        CreateMessage::new().embeds(get_lives_changed_embeds(
+                            request.player,
+                            lives,
+                            &events,
+                            locale,
+                        )),
+                    )
+                    .await?;
+                Ok(())
+            })
+        });
+
+        Ok(response)
+    }
+
+    /// The answer is part of the response, the moderator is the only one who sees it
+    async fn ask_question(
+        &self,
+        guild_id: GuildId,
+        request: AskQuestionRequest,
+    ) -> Result<Value, ApiError> {
+        let session = self.data.session(guild_id).await;
+        let locale = session.locale().await;
+        let (channel_id, moderator, player, drawn) = {
+            let mut lock = session.engine.lock().await;
+            let engine = lock.as_mut().ok_or(ApiError::NoActiveGame)?;
+
+            let player = request
+                .player
+                .or_else(|| engine.game().finale.as_ref().map(|finale| finale.on_turn()))
+                .ok_or_else(|| ApiError::BadRequest(t!(locale, "errors.api.missing_player")))?;
+            let drawn = draw_question(
+                engine,
+                &self.data.questions,
+                player,
+                request.category.as_deref(),
+                locale,
+            )
+            .map_err(|error| ApiError::Conflict(localize_error(error.as_ref(), locale)))?;
+
+            (
+                engine.game().channel_id,
+                engine.game().moderator,
+                player,
+                drawn,
+            )
+        };
+        self.data.persist_or_log().await;
+
+        let response = json!({
+            "player": player,
+            "round": drawn.round,
+            "question": drawn.question,
+        });
+        self.spawn_in_channel(guild_id, channel_id, move |channel| {
+            Box::pin(async move {
+                let member = channel.guild_id.member(channel.serenity, player).await?;
+                let embed = get_question_embed(
+                    &member,
+                    &drawn.question,
+                    drawn.round,
+                    drawn.in_finale,
+                    locale,
+                );
+                let prefix = format!("{}_answer_", channel.id);
+
+                let message = channel
+                    .channel_id
+                    .send_message(
+                        channel.serenity,
+                        CreateMessage::new()
+                            .embed(embed.clone())
+                            .components(get_judge_buttons(&prefix, locale)),
+                    )
+                    .await?;
+
+                collect_judgement(channel, moderator, message, embed, &prefix, drawn.index).await
+            })
+        });
+
+        Ok(response)
+    }
+
+    /// The messages of an action can keep running for a while (like a voting), so the response
+    /// doesn't wait for them
+    fn spawn_in_channel<F>(&self, guild_id: GuildId, channel_id: ChannelId, run: F)
+    where
+        F: for<'a> FnOnce(GameChannel<'a>) -> BoxFuture<'a, CmdRet> + Send + 'static,
+    {
+        let serenity = self.serenity.clone();
+        let data = self.data.clone();
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+
+        tokio::spawn(async move {
+            let channel = GameChannel {
+                serenity: &serenity,
+                data: &data,
+                guild_id,
+                channel_id,
+                id,
+            };
+
+            if let Err(error) = run(channel).await {
+                tracing::error!(error = %error, "control API failed to post to discord");
+            }
+        });
+    }
+}
+
+pub async fn serve(listener: TcpListener, api: Arc<ControlApi>) {
+    if let Ok(address) = listener.local_addr() {
+        tracing::info!(%address, "serving the control API");
+    }
+
+    loop {
+        let stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(error) => {
+                tracing::warn!(error = %error, "failed to accept a control API connection");
+                continue;
+            },
+        };
+
+        let api = api.clone();
+        tokio::spawn(async move {
+            if let Err(error) = handle_connection(stream, &api).await {
+                tracing::debug!(error = %error, "control API connection failed");
+            }
+        });
+    }
+}
+
+async fn handle_connection(stream: TcpStream, api: &ControlApi) -> std::io::Result<()> {
+    let mut stream = BufReader::new(stream);
+    let request = Request::read(&mut stream).await?;
+
+    let (status, body) = match api.handle(&request).await {
+        Ok(body) => ("200 OK", body),
+        Err(error) => {
+            let message = error.localize(api.locale(&request).await);
+            (error.status(), json!({ "error": message }))
+        },
+    };
+
+    http::respond(
+        &mut stream,
+        status,
+        "application/json",
+        body.to_string().as_bytes(),
+    )
+    .await
+}
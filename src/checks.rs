@@ -1,28 +1,40 @@
-use crate::{ContextEnum, Error};
-
-const NO_ACTIVE_GAME: &str = "Es gibt kein aktives Spiel.";
+use crate::{game::VoteChangePolicy, guild_locale, guild_session, t, ContextEnum, Error};
 
 pub async fn needs_active_game(ctx: ContextEnum<'_>) -> Result<bool, Error> {
-    match *ctx.data().game.lock().await {
-        Some(_) => Ok(true),
-        None => Err(NO_ACTIVE_GAME.into()),
+    let session = guild_session(ctx).await;
+    if session.engine.lock().await.is_some() {
+        Ok(true)
+    } else {
+        Err(t!(guild_locale(ctx).await, "checks.no_active_game").into())
     }
 }
 
+/// This assumes an active game
 pub async fn needs_active_voting(ctx: ContextEnum<'_>) -> Result<bool, Error> {
-    match &*ctx.data().voting.lock().await {
-        Some(_) => Ok(true),
-        None => Err("Es gibt kein aktives Voting.".into()),
+    let session = guild_session(ctx).await;
+    let lock = session.engine.lock().await;
+    let engine = lock.as_ref().expect("Expected an active game");
+
+    if engine.voting().is_some() {
+        Ok(true)
+    } else {
+        Err(t!(guild_locale(ctx).await, "checks.no_active_voting").into())
     }
 }
 
-/// This assumes an active voting
+/// This assumes an active voting. Passes if the voting allows changing a vote.
 pub async fn did_not_vote(ctx: ContextEnum<'_>) -> Result<bool, Error> {
-    let lock = ctx.data().voting.lock().await;
-    let voting = lock.as_ref().expect("Expected an active voting");
+    let session = guild_session(ctx).await;
+    let lock = session.engine.lock().await;
+    let voting = lock
+        .as_ref()
+        .and_then(|engine| engine.voting())
+        .expect("Expected an active voting");
 
-    if voting.map.contains_key(&ctx.author().id) {
-        Err("Du hast schon gevotet.".into())
+    if voting.map.contains_key(&ctx.author().id)
+        && voting.vote_changes.policy == VoteChangePolicy::Locked
+    {
+        Err(t!(guild_locale(ctx).await, "checks.already_voted").into())
     } else {
         Ok(true)
     }
@@ -30,41 +42,43 @@ pub async fn did_not_vote(ctx: ContextEnum<'_>) -> Result<bool, Error> {
 
 /// This assumes an active game
 pub async fn is_in_game(ctx: ContextEnum<'_>) -> Result<bool, Error> {
-    let lock = ctx.data().game.lock().await;
-    let game = lock.as_ref().expect("Expected an active game");
+    let session = guild_session(ctx).await;
+    let lock = session.engine.lock().await;
+    let game = lock.as_ref().expect("Expected an active game").game();
 
-    if !game.members.contains_key(&ctx.author().id) {
-        Err("Du bist diesem Spiel nicht beigetreten.".into())
+    if !game.contains_player(ctx.author().id) {
+        Err(t!(guild_locale(ctx).await, "checks.not_in_game").into())
     } else {
         Ok(true)
     }
 }
 
-/// This check .unwraps the [Game::moderator] field, so it assumes an active game
+/// This assumes an active game
 pub async fn is_game_moderator(ctx: ContextEnum<'_>) -> Result<bool, Error> {
-    let lock = ctx.data().game.lock().await;
-    let game = lock.as_ref().expect("Expected an active game");
+    let session = guild_session(ctx).await;
+    let lock = session.engine.lock().await;
+    let game = lock.as_ref().expect("Expected an active game").game();
 
-    if game.moderator.user.id == ctx.author().id {
+    if game.moderator == ctx.author().id {
         Ok(true)
     } else {
-        Err("Du bist nicht der Moderator dieses Spiels.".into())
+        Err(t!(guild_locale(ctx).await, "checks.not_moderator").into())
     }
 }
 
 /// Assumes an active game, an active voting, and that the player is in the game
-pub async fn author_is_alive(ctx: ContextEnum<'_>) -> Result<bool, Error> {
-    let lock = ctx.data().game.lock().await;
-    let game = lock.as_ref().expect("Expected an active game");
+pub async fn author_can_vote(ctx: ContextEnum<'_>) -> Result<bool, Error> {
+    let session = guild_session(ctx).await;
+    let lock = session.engine.lock().await;
+    let game = lock.as_ref().expect("Expected an active game").game();
 
-    if *game
-        .members
-        .get(&ctx.author().id)
-        .expect("Expected user to be available in `author_is_alive`")
-        > 0
+    // eliminated players might still have a ghost vote
+    if game
+        .can_vote(ctx.author().id)
+        .expect("Expected user to be available in `author_can_vote`")
     {
         Ok(true)
     } else {
-        Err("Du bist ausgeschieden.".into())
+        Err(t!(guild_locale(ctx).await, "checks.eliminated").into())
     }
 }
@@ -0,0 +1,79 @@
+use poise::{
+    command,
+    serenity_prelude::{CreateEmbed, Member, Mentionable},
+    CreateReply,
+};
+
+use crate::{
+    checks::{is_game_moderator, needs_active_game, needs_active_voting},
+    embed_color,
+    engine::Command,
+    game::AudienceVote,
+    guild_locale,
+    guild_session,
+    t,
+    CmdRet,
+    Context,
+};
+
+#[command(slash_command, rename = "set-audience-vote", guild_only, check = needs_active_game, check = is_game_moderator)]
+pub async fn set_audience_vote(
+    ctx: Context<'_>,
+    #[rename = "aktiv"] enabled: bool,
+    #[rename = "votes-pro-spieler-vote"]
+    #[min = 1]
+    votes_per_player_vote: Option<u32>,
+) -> CmdRet {
+    let audience_vote = AudienceVote {
+        enabled,
+        votes_per_player_vote,
+    };
+
+    {
+        let session = guild_session(ctx).await;
+        let mut lock = session.engine.lock().await;
+        let engine = lock.as_mut().unwrap();
+
+        engine.handle(Command::SetAudienceVote { audience_vote })?;
+    }
+
+    let locale = guild_locale(ctx).await;
+    let description = match (enabled, votes_per_player_vote) {
+        (false, _) => t!(locale, "audience.disabled"),
+        (true, None) => t!(locale, "audience.enabled_shown_only"),
+        (true, Some(votes)) => t!(locale, "audience.enabled_counted", votes = votes),
+    };
+
+    let embed = CreateEmbed::default()
+        .title(t!(locale, "audience.title"))
+        .description(description)
+        .color(embed_color());
+
+    ctx.send(CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
+
+#[command(slash_command, rename = "audience-vote", guild_only, ephemeral, check = needs_active_game, check = needs_active_voting)]
+pub async fn audience_vote(ctx: Context<'_>, #[rename = "user"] member: Member) -> CmdRet {
+    {
+        let session = guild_session(ctx).await;
+        let mut lock = session.engine.lock().await;
+        let engine = lock.as_mut().unwrap();
+
+        engine.handle(Command::CastAudienceVote {
+            voter: ctx.author().id,
+            target: member.user.id,
+        })?;
+    }
+
+    let embed = CreateEmbed::default()
+        .description(t!(
+            guild_locale(ctx).await,
+            "audience.voted",
+            player = member.mention()
+        ))
+        .color(embed_color());
+
+    ctx.send(CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
@@ -0,0 +1,114 @@
+use poise::{command, serenity_prelude::CreateEmbed, CreateReply};
+
+use crate::{
+    config::{GameConfig, GameConfigOverrides},
+    embed_color,
+    guild_locale,
+    guild_session,
+    i18n::{tr, Locale},
+    t,
+    CmdRet,
+    Context,
+};
+
+// every setting that isn't given stays as it is, without any the current ones are only shown
+#[command(slash_command, guild_only, default_member_permissions = "MANAGE_GUILD")]
+pub async fn config(
+    ctx: Context<'_>,
+    #[rename = "leben"]
+    #[min = 1]
+    #[max = 99]
+    lives: Option<i32>,
+    #[rename = "bestaetigung"]
+    #[min = 10]
+    #[max = 86400]
+    confirm_timeout: Option<u32>,
+    #[rename = "lobby"]
+    #[min = 10]
+    #[max = 86400]
+    lobby_timeout: Option<u32>,
+    #[rename = "gleichstand"]
+    #[min = 10]
+    #[max = 86400]
+    tie_decision_timeout: Option<u32>,
+    #[rename = "antwort"]
+    #[min = 10]
+    #[max = 86400]
+    answer_timeout: Option<u32>,
+    #[rename = "zuruecksetzen"] reset: Option<bool>,
+) -> CmdRet {
+    let overrides = {
+        let session = guild_session(ctx).await;
+        let mut overrides = session.config.lock().await;
+
+        let mut changed = if reset.unwrap_or(false) {
+            GameConfigOverrides::default()
+        } else {
+            *overrides
+        };
+        changed.lives = lives.or(changed.lives);
+        changed.confirm_timeout = confirm_timeout.or(changed.confirm_timeout);
+        changed.lobby_timeout = lobby_timeout.or(changed.lobby_timeout);
+        changed.tie_decision_timeout = tie_decision_timeout.or(changed.tie_decision_timeout);
+        changed.answer_timeout = answer_timeout.or(changed.answer_timeout);
+
+        // Discord checks the ranges as well, but the config is only ever changed as a whole
+        changed.apply(ctx.data().config.game).validate()?;
+        *overrides = changed;
+        changed
+    };
+
+    let locale = guild_locale(ctx).await;
+    let embed = get_config_embed(&overrides, &ctx.data().config.game, locale);
+
+    ctx.send(CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
+
+fn get_config_embed(
+    overrides: &GameConfigOverrides,
+    defaults: &GameConfig,
+    locale: Locale,
+) -> CreateEmbed {
+    let config = overrides.apply(*defaults);
+    let setting = |key: &'static str, value: String, overridden: bool| {
+        let source = if overridden {
+            tr(locale, "config.overridden")
+        } else {
+            tr(locale, "config.default")
+        };
+        (tr(locale, key), format!("{value} ({source})"), true)
+    };
+    let seconds = |seconds: u32| t!(locale, "config.seconds", seconds = seconds);
+
+    CreateEmbed::default()
+        .title(t!(locale, "config.title"))
+        .fields([
+            setting(
+                "config.lives",
+                config.lives.to_string(),
+                overrides.lives.is_some(),
+            ),
+            setting(
+                "config.confirm_timeout",
+                seconds(config.confirm_timeout),
+                overrides.confirm_timeout.is_some(),
+            ),
+            setting(
+                "config.lobby_timeout",
+                seconds(config.lobby_timeout),
+                overrides.lobby_timeout.is_some(),
+            ),
+            setting(
+                "config.tie_decision_timeout",
+                seconds(config.tie_decision_timeout),
+                overrides.tie_decision_timeout.is_some(),
+            ),
+            setting(
+                "config.answer_timeout",
+                seconds(config.answer_timeout),
+                overrides.answer_timeout.is_some(),
+            ),
+        ])
+        .color(embed_color())
+}
@@ -0,0 +1,114 @@
+use chrono::{DateTime, Utc};
+use poise::{
+    command,
+    serenity_prelude::{ChannelId, CreateAttachment, CreateEmbed, UserId},
+    CreateReply,
+};
+use serde::Serialize;
+
+use crate::{
+    checks::{is_game_moderator, needs_active_game},
+    embed_color,
+    engine::{Event, LogEntry, Phase},
+    guild_locale,
+    guild_session,
+    t,
+    CmdRet,
+    Context,
+};
+
+/// Bumped whenever the layout of [GameExport] or [crate::engine::Event] changes
+pub const EXPORT_SCHEMA_VERSION: u32 = 1;
+
+/// The content of the file `/export-game` uploads, see the README for the schema
+#[derive(Debug, Serialize)]
+pub struct GameExport {
+    pub schema_version: u32,
+    pub exported_at: DateTime<Utc>,
+    pub creator: UserId,
+    pub moderator: UserId,
+    pub channel_id: ChannelId,
+    pub phase: Phase,
+    pub round: u32,
+    pub log: Vec<LogEntry>,
+}
+
+/// Leaves out who voted for whom in anonymous votings, as an export is easily passed on. The
+/// counts in [Event::VotingResolved] stay.
+pub fn redact_anonymous_votes(log: &[LogEntry]) -> Vec<LogEntry> {
+    let mut anonymous = false;
+    let mut redacted = Vec::with_capacity(log.len());
+
+    for entry in log {
+        let mut entry = entry.clone();
+
+        match &mut entry.event {
+            // a runoff takes over the setting of its voting
+            Event::VotingStarted {
+                anonymous: started_anonymous,
+                ..
+            } => anonymous = *started_anonymous,
+            Event::VoteCast { .. }
+            | Event::VoteChanged { .. }
+            | Event::AudienceVoteCast { .. }
+            | Event::TwitchVoteCast { .. }
+                if anonymous =>
+            {
+                continue;
+            },
+            Event::VotingResolved {
+                votes,
+                anonymous: true,
+                changes,
+                ..
+            } => {
+                votes.clear();
+                changes.clear();
+            },
+            _ => (),
+        }
+
+        redacted.push(entry);
+    }
+
+    redacted
+}
+
+// Only for the moderator, as the log shows what happened behind the scenes
+#[command(slash_command, rename = "export-game", guild_only, ephemeral, check = needs_active_game, check = is_game_moderator)]
+pub async fn export_game(ctx: Context<'_>) -> CmdRet {
+    let (json, events) = {
+        let session = guild_session(ctx).await;
+        let lock = session.engine.lock().await;
+        let engine = lock.as_ref().expect("Expected an active game");
+        let game = engine.game();
+
+        let export = GameExport {
+            schema_version: EXPORT_SCHEMA_VERSION,
+            exported_at: Utc::now(),
+            creator: game.creator,
+            moderator: game.moderator,
+            channel_id: game.channel_id,
+            phase: engine.phase(),
+            round: game.round,
+            log: redact_anonymous_votes(&game.log),
+        };
+
+        (serde_json::to_vec_pretty(&export)?, game.log.len())
+    };
+
+    let locale = guild_locale(ctx).await;
+    let embed = CreateEmbed::default()
+        .title(t!(locale, "export.title"))
+        .description(t!(locale, "export.description", events = events))
+        .color(embed_color());
+
+    ctx.send(
+        CreateReply::default()
+            .embed(embed)
+            .attachment(CreateAttachment::bytes(json, "ddf_game.json")),
+    )
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,105 @@
+use poise::{
+    command,
+    serenity_prelude::{CreateEmbed, Mentionable, UserId},
+    CreateReply,
+};
+
+use crate::{
+    checks::{is_game_moderator, needs_active_game},
+    embed_color,
+    engine::Command,
+    game::{Finale, FinaleRules},
+    guild_locale,
+    guild_session,
+    i18n::Locale,
+    t,
+    CmdRet,
+    Context,
+};
+
+// every setting that isn't given stays as it is
+#[command(slash_command, rename = "set-finale", guild_only, check = needs_active_game, check = is_game_moderator)]
+pub async fn set_finale(
+    ctx: Context<'_>,
+    #[rename = "aktiv"] enabled: Option<bool>,
+    #[rename = "fragen"]
+    #[min = 1]
+    #[max = 50]
+    questions_per_player: Option<u32>,
+) -> CmdRet {
+    let finale_rules = {
+        let session = guild_session(ctx).await;
+        let mut lock = session.engine.lock().await;
+        let engine = lock.as_mut().unwrap();
+
+        let current = engine.game().finale_rules;
+        let finale_rules = FinaleRules {
+            enabled: enabled.unwrap_or(current.enabled),
+            questions_per_player: questions_per_player.unwrap_or(current.questions_per_player),
+        };
+
+        engine.handle(Command::SetFinaleRules { finale_rules })?;
+        finale_rules
+    };
+
+    let locale = guild_locale(ctx).await;
+    let description = if finale_rules.enabled {
+        t!(
+            locale,
+            "finale.enabled",
+            questions = finale_rules.questions_per_player
+        )
+    } else {
+        t!(locale, "finale.disabled")
+    };
+
+    let embed = CreateEmbed::default()
+        .title(t!(locale, "finale.title"))
+        .description(description)
+        .color(embed_color());
+
+    ctx.send(CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
+
+pub fn get_finale_started_embed(
+    players: [UserId; 2],
+    questions_per_player: u32,
+    locale: Locale,
+) -> CreateEmbed {
+    CreateEmbed::default()
+        .title(t!(locale, "finale.started_title"))
+        .description(t!(
+            locale,
+            "finale.started",
+            first = players[0].mention(),
+            second = players[1].mention(),
+            questions = questions_per_player
+        ))
+        .color(embed_color())
+}
+
+/// The correct answers of both finalists, e.g. "@A 3/4 · @B 2/4"
+pub fn format_finale_score(finale: &Finale) -> String {
+    finale
+        .players
+        .iter()
+        .map(|player| {
+            let (answered, correct) = finale.score(*player);
+            format!("{} {correct}/{answered}", player.mention())
+        })
+        .collect::<Vec<_>>()
+        .join(" · ")
+}
+
+pub fn get_finale_winner_embed(finale: &Finale, winner: UserId, locale: Locale) -> CreateEmbed {
+    CreateEmbed::default()
+        .title(t!(locale, "finale.winner_title"))
+        .description(t!(locale, "finale.winner", player = winner.mention()))
+        .field(
+            t!(locale, "standings.final"),
+            format_finale_score(finale),
+            false,
+        )
+        .color(embed_color())
+}
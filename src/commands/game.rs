@@ -1,5 +1,3 @@
-use std::{collections::HashMap, time::Duration};
-
 use poise::{
     command,
     serenity_prelude::{
@@ -13,6 +11,8 @@ use poise::{
         CreateEmbed,
         CreateInteractionResponse,
         CreateInteractionResponseMessage,
+        CreateMessage,
+        EditMessage,
         Member,
         Mentionable,
     },
@@ -21,10 +21,20 @@ use poise::{
 
 use crate::{
     checks::{is_game_moderator, needs_active_game},
-    game::Game,
+    commands::{
+        finale::{format_finale_score, get_finale_started_embed},
+        question::format_answer_stats,
+        vote::get_game_finished_embed,
+    },
+    embed_color,
+    engine::{Command, Event, GameEngine, Phase},
+    guild_config,
+    guild_locale,
+    guild_session,
+    i18n::{tr, Localize},
+    t,
     CmdRet,
     Context,
-    DEFAULT_COLOR,
 };
 
 fn get_remaining_lives_string(number_of_lives: i32) -> String {
@@ -34,16 +44,37 @@ fn get_remaining_lives_string(number_of_lives: i32) -> String {
 #[command(slash_command, rename = "start-game", guild_only)]
 pub async fn start_game(
     ctx: Context<'_>,
-    #[description = "Der Moderator des Spiels"] moderator: Member,
+    moderator: Member,
+    lobby: Option<bool>,
+    #[rename = "max-spieler"]
+    #[min = 2]
+    max_players: Option<u32>,
 ) -> CmdRet {
-    if ctx.data().game.lock().await.is_some() {
-        prompt_override_game(ctx, moderator).await
+    let lobby = lobby.unwrap_or(false);
+
+    // a finished game can be replaced right away
+    let game_is_running = guild_session(ctx)
+        .await
+        .engine
+        .lock()
+        .await
+        .as_ref()
+        .is_some_and(|engine| engine.phase() != Phase::Finished);
+
+    if game_is_running {
+        prompt_override_game(ctx, moderator, lobby, max_players).await
     } else {
-        create_new_game(ctx, moderator, None).await
+        create_new_game(ctx, moderator, lobby, max_players, None).await
     }
 }
 
-pub async fn prompt_override_game(ctx: Context<'_>, moderator: Member) -> CmdRet {
+pub async fn prompt_override_game(
+    ctx: Context<'_>,
+    moderator: Member,
+    lobby: bool,
+    max_players: Option<u32>,
+) -> CmdRet {
+    let locale = guild_locale(ctx).await;
     let ctx_id = ctx.id().to_string();
 
     let (yes_id, no_id) = (format!("{ctx_id}_yes"), format!("{ctx_id}_no"));
@@ -52,15 +83,15 @@ pub async fn prompt_override_game(ctx: Context<'_>, moderator: Member) -> CmdRet
         CreateReply::default()
             .embed(
                 CreateEmbed::default()
-                    .description("Es gibt ein laufendes Spiel.\nMöchtest du es überschreiben?")
+                    .description(t!(locale, "game.override"))
                     .color(Color::RED),
             )
             .components(vec![CreateActionRow::Buttons(vec![
                 CreateButton::new(&no_id)
-                    .label("Nein")
+                    .label(t!(locale, "game.no"))
                     .style(ButtonStyle::Danger),
                 CreateButton::new(&yes_id)
-                    .label("Ja")
+                    .label(t!(locale, "game.yes"))
                     .style(ButtonStyle::Success),
             ])]),
     )
@@ -68,13 +99,15 @@ pub async fn prompt_override_game(ctx: Context<'_>, moderator: Member) -> CmdRet
 
     let mut collector = ComponentInteractionCollector::new(ctx.serenity_context())
         .author_id(ctx.author().id)
-        .timeout(Duration::from_secs(60))
+        .timeout(guild_config(ctx).await.confirm_timeout())
         .filter(move |interaction| interaction.data.custom_id.starts_with(&ctx_id))
         .stream();
 
     if let Some(press) = collector.next().await {
         match &press.data.custom_id {
-            id if id == &yes_id => create_new_game(ctx, moderator, Some(press)).await?,
+            id if id == &yes_id => {
+                create_new_game(ctx, moderator, lobby, max_players, Some(press)).await?
+            },
             _ => {
                 press
                     .create_response(
@@ -83,7 +116,7 @@ pub async fn prompt_override_game(ctx: Context<'_>, moderator: Member) -> CmdRet
                             CreateInteractionResponseMessage::new()
                                 .embed(
                                     CreateEmbed::default()
-                                        .description("Abgebrochen")
+                                        .description(t!(locale, "game.cancelled"))
                                         .color(Color::RED),
                                 )
                                 .components(vec![]),
@@ -100,26 +133,26 @@ pub async fn prompt_override_game(ctx: Context<'_>, moderator: Member) -> CmdRet
 pub async fn create_new_game(
     ctx: Context<'_>,
     moderator: Member,
+    lobby: bool,
+    max_players: Option<u32>,
     edit_on: Option<ComponentInteraction>,
 ) -> CmdRet {
-    let mut game = ctx.data().game.lock().await;
-    let mut vote = ctx.data().voting.lock().await;
-
+    let locale = guild_locale(ctx).await;
     let embed = CreateEmbed::default()
-        .title("Spiel gestartet")
-        .description(format!(
-            "✅ Das Spiel wurde mit {} als Moderator wurde erfolgreich gestartet",
-            moderator.mention()
-        ))
-        .color(DEFAULT_COLOR);
+        .title(t!(locale, "game.started_title"))
+        .description(t!(locale, "game.started", moderator = moderator.mention()))
+        .color(embed_color());
 
-    *game = Some(Game {
-        moderator,
-        creator: *ctx.interaction.member.as_ref().unwrap().clone(),
-        members: HashMap::new(),
-    });
+    {
+        let session = guild_session(ctx).await;
+        let mut lock = session.engine.lock().await;
 
-    *vote = None;
+        let mut engine = GameEngine::new(ctx.author().id, moderator.user.id, ctx.channel_id());
+        if max_players.is_some() {
+            engine.handle(Command::SetMaxPlayers { max_players })?;
+        }
+        *lock = Some(engine);
+    }
 
     if let Some(interaction) = edit_on {
         interaction
@@ -136,78 +169,266 @@ pub async fn create_new_game(
         ctx.send(CreateReply::default().embed(embed)).await?;
     }
 
+    if lobby {
+        // this command keeps running while the lobby is open, so `post_command` would be too late
+        ctx.data().persist_or_log().await;
+        run_lobby(ctx).await?;
+    }
+
+    Ok(())
+}
+
+/// Lets players join and leave via buttons until the moderator closes the lobby
+async fn run_lobby(ctx: Context<'_>) -> CmdRet {
+    let locale = guild_locale(ctx).await;
+    let config = guild_config(ctx).await;
+    let prefix = format!("{}_lobby_", ctx.id());
+    let (join_id, leave_id, close_id) = (
+        format!("{prefix}join"),
+        format!("{prefix}leave"),
+        format!("{prefix}close"),
+    );
+
+    let buttons = vec![CreateActionRow::Buttons(vec![
+        CreateButton::new(&join_id)
+            .label(t!(locale, "lobby.join"))
+            .style(ButtonStyle::Success),
+        CreateButton::new(&leave_id)
+            .label(t!(locale, "lobby.leave"))
+            .style(ButtonStyle::Secondary),
+        CreateButton::new(&close_id)
+            .label(t!(locale, "lobby.close"))
+            .style(ButtonStyle::Danger),
+    ])];
+
+    let Some(embed) = get_lobby_embed(ctx, tr(locale, "lobby.open")).await else {
+        return Ok(());
+    };
+    let mut message = ctx
+        .channel_id()
+        .send_message(ctx, CreateMessage::new().embed(embed).components(buttons))
+        .await?;
+
+    let mut collector = ComponentInteractionCollector::new(ctx.serenity_context())
+        .message_id(message.id)
+        // `/add-user` works after that as well
+        .timeout(config.lobby_timeout())
+        .stream();
+
+    while let Some(press) = collector.next().await {
+        let player = press.user.id;
+        let command = match &press.data.custom_id {
+            id if id == &join_id => Command::Join {
+                player,
+                lives: config.lives,
+            },
+            id if id == &leave_id => Command::Leave { player },
+            _ => Command::CloseLobby,
+        };
+        let closing = matches!(command, Command::CloseLobby);
+
+        let result = {
+            let session = guild_session(ctx).await;
+            let mut lock = session.engine.lock().await;
+            match lock.as_mut() {
+                Some(engine) if closing && engine.game().moderator != player => {
+                    Err(t!(locale, "lobby.only_moderator"))
+                },
+                // votes and the finale depend on the players once the game has started
+                Some(engine)
+                    if matches!(command, Command::Leave { .. })
+                        && engine.phase() != Phase::Lobby =>
+                {
+                    Err(t!(locale, "lobby.game_started"))
+                },
+                Some(engine) => engine.handle(command).map_err(|err| err.localize(locale)),
+                None => Err(t!(locale, "checks.no_active_game")),
+            }
+        };
+
+        if let Err(err) = result {
+            press
+                .create_response(
+                    ctx,
+                    CreateInteractionResponse::Message(
+                        CreateInteractionResponseMessage::new()
+                            .embed(
+                                CreateEmbed::default()
+                                    .title(t!(locale, "errors.title"))
+                                    .description(err)
+                                    .color(Color::DARK_RED),
+                            )
+                            .ephemeral(true),
+                    ),
+                )
+                .await?;
+            continue;
+        }
+
+        ctx.data().persist_or_log().await;
+
+        let description = tr(
+            locale,
+            if closing {
+                "lobby.closed"
+            } else {
+                "lobby.open"
+            },
+        );
+        let Some(embed) = get_lobby_embed(ctx, description).await else {
+            break;
+        };
+        let mut response = CreateInteractionResponseMessage::new().embed(embed);
+        if closing {
+            response = response.components(vec![]);
+        }
+        press
+            .create_response(ctx, CreateInteractionResponse::UpdateMessage(response))
+            .await?;
+
+        if closing {
+            return Ok(());
+        }
+    }
+
+    let mut edit = EditMessage::new().components(vec![]);
+    if let Some(embed) = get_lobby_embed(ctx, tr(locale, "lobby.expired")).await {
+        edit = edit.embed(embed);
+    }
+    message.edit(ctx, edit).await?;
+
     Ok(())
 }
 
+/// Returns `None` if there is no game anymore
+async fn get_lobby_embed(ctx: Context<'_>, description: &str) -> Option<CreateEmbed> {
+    let session = guild_session(ctx).await;
+    let locale = session.locale().await;
+    let lock = session.engine.lock().await;
+    let game = lock.as_ref()?.game();
+
+    let count = match game.max_players {
+        Some(max_players) => format!("{}/{max_players}", game.members.len()),
+        None => game.members.len().to_string(),
+    };
+    let mut players = game.members.keys().collect::<Vec<_>>();
+    players.sort();
+    let players = if players.is_empty() {
+        t!(locale, "lobby.nobody")
+    } else {
+        players
+            .iter()
+            .map(|player| player.mention().to_string())
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    Some(
+        CreateEmbed::default()
+            .title(t!(locale, "lobby.title"))
+            .description(description)
+            .field(t!(locale, "lobby.players", count = count), players, false)
+            .color(embed_color()),
+    )
+}
+
 #[command(slash_command, rename = "add-user", guild_only, check = needs_active_game, check = is_game_moderator)]
 pub async fn add_user(
     ctx: Context<'_>,
-    #[description = "Der User der hinzugefügt werden soll"]
-    #[rename = "user"]
-    member: Member,
+    #[rename = "user"] member: Member,
     lives: Option<i32>,
 ) -> CmdRet {
     {
-        let mut lock = ctx.data().game.lock().await;
-        let game = lock.as_mut().unwrap();
-
-        game.add_player(member.user.id, lives.unwrap_or(3))?;
+        let session = guild_session(ctx).await;
+        let mut lock = session.engine.lock().await;
+        let engine = lock.as_mut().unwrap();
+
+        engine.handle(Command::Join {
+            player: member.user.id,
+            lives: lives.unwrap_or(guild_config(ctx).await.lives),
+        })?;
     }
 
     ctx.send(
         CreateReply::default().embed(
             CreateEmbed::default()
-                .description(format!("➕ User {} wurde hinzugefügt", member.mention()))
-                .color(DEFAULT_COLOR),
+                .description(t!(
+                    guild_locale(ctx).await,
+                    "game.user_added",
+                    player = member.mention()
+                ))
+                .color(embed_color()),
         ),
     )
     .await?;
     Ok(())
 }
 
-#[command(slash_command, rename = "remove-user", guild_only, check = is_game_moderator, check = needs_active_game)]
-pub async fn remove_user(
-    ctx: Context<'_>,
-    #[description = "Der User der entfernt werden soll"]
-    #[rename = "user"]
-    member: Member,
-) -> CmdRet {
-    {
-        let mut lock = ctx.data().game.lock().await;
-        let game = lock.as_mut().unwrap();
-
-        game.remove_player(ctx.author().id)?;
+#[command(slash_command, rename = "remove-user", guild_only, check = needs_active_game, check = is_game_moderator)]
+pub async fn remove_user(ctx: Context<'_>, #[rename = "user"] member: Member) -> CmdRet {
+    let events = {
+        let session = guild_session(ctx).await;
+        let mut lock = session.engine.lock().await;
+        let engine = lock.as_mut().unwrap();
+
+        let events = engine.handle(Command::Leave {
+            player: member.user.id,
+        })?;
+        session.record_finished_game(engine, &events).await;
+        events
+    };
+
+    // removing a player can start the finale or end the game
+    let locale = guild_locale(ctx).await;
+    let mut reply = CreateReply::default().embed(
+        CreateEmbed::default()
+            .description(t!(locale, "game.user_removed", player = member.mention()))
+            .color(embed_color()),
+    );
+    for event in events {
+        match event {
+            Event::FinaleStarted {
+                players,
+                questions_per_player,
+            } => {
+                reply = reply.embed(get_finale_started_embed(
+                    players,
+                    questions_per_player,
+                    locale,
+                ));
+            },
+            Event::GameFinished { winner, standings } => {
+                reply = reply.embed(get_game_finished_embed(winner, &standings, locale));
+            },
+            _ => (),
+        }
     }
 
-    ctx.send(
-        CreateReply::default().embed(
-            CreateEmbed::default()
-                .description(format!("➖ User {} wurde entfernt", member.mention()))
-                .color(DEFAULT_COLOR),
-        ),
-    )
-    .await?;
+    ctx.send(reply).await?;
     Ok(())
 }
 
 #[command(slash_command, rename = "show-game", guild_only, check = needs_active_game)]
 pub async fn show_game(ctx: Context<'_>) -> CmdRet {
-    let lock = ctx.data().game.lock().await;
-    let game = lock.as_ref().expect("Expected an active game");
+    let session = guild_session(ctx).await;
+    let locale = session.locale().await;
+    let lock = session.engine.lock().await;
+    let game = lock.as_ref().expect("Expected an active game").game();
 
     let users = &game.members;
 
     let mut description = String::new();
 
     if users.is_empty() {
-        description.push_str("Es sind keine User in diesem Spiel")
+        description.push_str(tr(locale, "game.no_players"))
     } else {
         for (user, lives) in users {
+            let stats = game.answer_stats(*user, None);
             let user = user.to_user(ctx).await?;
             if *lives == 0 {
                 description.push_str(
                     format!(
-                        "~~{} ({})\n\n~~",
+                        "~~{} ({})~~",
                         user.mention(),
                         get_remaining_lives_string(*lives)
                     )
@@ -216,19 +437,31 @@ pub async fn show_game(ctx: Context<'_>) -> CmdRet {
             } else {
                 description.push_str(
                     format!(
-                        "{} ({})\n\n",
+                        "{} ({})",
                         user.mention(),
                         get_remaining_lives_string(*lives)
                     )
                     .as_str(),
                 )
             }
+
+            if stats.asked > 0 {
+                description.push_str(format!("\n{}", format_answer_stats(&stats)).as_str());
+            }
+            description.push_str("\n\n");
         }
     }
 
-    let embed = CreateEmbed::default()
+    let mut embed = CreateEmbed::default()
         .description(description)
-        .color(DEFAULT_COLOR);
+        .color(embed_color());
+    if let Some(finale) = &game.finale {
+        embed = embed.field(
+            t!(locale, "finale.title"),
+            format_finale_score(finale),
+            false,
+        );
+    }
 
     ctx.send(CreateReply::default().embed(embed)).await?;
 
@@ -237,6 +470,7 @@ pub async fn show_game(ctx: Context<'_>) -> CmdRet {
 
 #[command(slash_command, rename = "end-game", guild_only, check = needs_active_game)]
 pub async fn end_game(ctx: Context<'_>) -> CmdRet {
+    let locale = guild_locale(ctx).await;
     let ctx_id = ctx.id().to_string();
 
     let (yes_id, no_id) = (format!("{ctx_id}_yes"), format!("{ctx_id}_no"));
@@ -245,15 +479,15 @@ pub async fn end_game(ctx: Context<'_>) -> CmdRet {
         CreateReply::default()
             .embed(
                 CreateEmbed::default()
-                    .description("Möchtest du das laufende Spiel wirklich beenden?")
+                    .description(t!(locale, "game.end_confirm"))
                     .color(Color::RED),
             )
             .components(vec![CreateActionRow::Buttons(vec![
                 CreateButton::new(&no_id)
-                    .label("Nein")
+                    .label(t!(locale, "game.no"))
                     .style(ButtonStyle::Danger),
                 CreateButton::new(&yes_id)
-                    .label("Ja")
+                    .label(t!(locale, "game.yes"))
                     .style(ButtonStyle::Success),
             ])]),
     )
@@ -261,7 +495,7 @@ pub async fn end_game(ctx: Context<'_>) -> CmdRet {
 
     let mut collector = ComponentInteractionCollector::new(ctx.serenity_context())
         .author_id(ctx.author().id)
-        .timeout(Duration::from_secs(60))
+        .timeout(guild_config(ctx).await.confirm_timeout())
         .filter(move |interaction| interaction.data.custom_id.starts_with(&ctx_id))
         .stream();
 
@@ -269,8 +503,9 @@ pub async fn end_game(ctx: Context<'_>) -> CmdRet {
         match &press.data.custom_id {
             id if id == &yes_id => {
                 {
-                    let mut game = ctx.data().game.lock().await;
-                    *game = None;
+                    let session = guild_session(ctx).await;
+                    let mut engine = session.engine.lock().await;
+                    *engine = None;
                 }
 
                 press
@@ -280,8 +515,8 @@ pub async fn end_game(ctx: Context<'_>) -> CmdRet {
                             CreateInteractionResponseMessage::new()
                                 .embed(
                                     CreateEmbed::default()
-                                        .description("Das Spiel wurde erfolgreich beendet")
-                                        .color(DEFAULT_COLOR),
+                                        .description(t!(locale, "game.ended"))
+                                        .color(embed_color()),
                                 )
                                 .components(vec![]),
                         ),
@@ -296,7 +531,7 @@ pub async fn end_game(ctx: Context<'_>) -> CmdRet {
                             CreateInteractionResponseMessage::new()
                                 .embed(
                                     CreateEmbed::default()
-                                        .description("Abgebrochen")
+                                        .description(t!(locale, "game.cancelled"))
                                         .color(Color::RED),
                                 )
                                 .components(vec![]),
@@ -0,0 +1,38 @@
+use poise::{command, serenity_prelude::CreateEmbed, CreateReply};
+
+use crate::{
+    checks::{is_game_moderator, needs_active_game},
+    embed_color,
+    engine::Command,
+    game::GhostVote,
+    guild_locale,
+    guild_session,
+    i18n::Localize,
+    t,
+    CmdRet,
+    Context,
+};
+
+#[command(slash_command, rename = "set-ghost-vote", guild_only, check = needs_active_game, check = is_game_moderator)]
+pub async fn set_ghost_vote(ctx: Context<'_>, #[rename = "regel"] ghost_vote: GhostVote) -> CmdRet {
+    {
+        let session = guild_session(ctx).await;
+        let mut lock = session.engine.lock().await;
+        let engine = lock.as_mut().unwrap();
+
+        engine.handle(Command::SetGhostVote { ghost_vote })?;
+    }
+
+    let locale = guild_locale(ctx).await;
+    let embed = CreateEmbed::default()
+        .title(t!(locale, "ghost_vote.title"))
+        .description(t!(
+            locale,
+            "ghost_vote.changed",
+            rule = ghost_vote.localize(locale)
+        ))
+        .color(embed_color());
+
+    ctx.send(CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
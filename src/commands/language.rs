@@ -0,0 +1,22 @@
+use poise::{command, serenity_prelude::CreateEmbed, CreateReply};
+
+use crate::{embed_color, guild_session, i18n::Locale, t, CmdRet, Context};
+
+// the language is a setting of the guild, so it outlives the game and doesn't need one
+#[command(
+    slash_command,
+    rename = "set-language",
+    guild_only,
+    default_member_permissions = "MANAGE_GUILD"
+)]
+pub async fn set_language(ctx: Context<'_>, #[rename = "sprache"] locale: Locale) -> CmdRet {
+    *guild_session(ctx).await.locale.lock().await = locale;
+
+    let embed = CreateEmbed::default()
+        .title(t!(locale, "language.title"))
+        .description(t!(locale, "language.changed"))
+        .color(embed_color());
+
+    ctx.send(CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
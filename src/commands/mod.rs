@@ -1,3 +1,53 @@
+pub mod audience;
+pub mod config;
+pub mod export;
+pub mod finale;
 pub mod game;
+pub mod ghost_vote;
+pub mod language;
+pub mod question;
+pub mod rules;
 pub mod set_lives;
+pub mod standings;
+pub mod stats;
+pub mod tie_break;
 pub mod vote;
+
+use std::sync::Arc;
+
+use poise::serenity_prelude::{self, ChannelId, GuildId};
+
+use crate::{
+    data::{Data, Session},
+    Context,
+};
+
+/// The channel a game is played in. Messages that keep running after a command answered, like
+/// the voting message, are driven through this, so the control API can start them as well.
+#[derive(Clone, Copy)]
+pub struct GameChannel<'a> {
+    pub serenity: &'a serenity_prelude::Context,
+    pub data: &'a Data,
+    pub guild_id: GuildId,
+    pub channel_id: ChannelId,
+    // keeps the custom IDs of the buttons apart
+    pub id: u64,
+}
+
+impl GameChannel<'_> {
+    pub async fn session(&self) -> Arc<Session> {
+        self.data.session(self.guild_id).await
+    }
+}
+
+impl<'a> From<Context<'a>> for GameChannel<'a> {
+    fn from(ctx: Context<'a>) -> Self {
+        Self {
+            serenity: ctx.serenity_context(),
+            data: ctx.data(),
+            guild_id: ctx.guild_id().expect("guild ID should be set"),
+            channel_id: ctx.channel_id(),
+            id: ctx.id(),
+        }
+    }
+}
@@ -0,0 +1,484 @@
+use poise::{
+    command,
+    serenity_prelude::{
+        futures::StreamExt,
+        ButtonStyle,
+        Color,
+        ComponentInteractionCollector,
+        CreateActionRow,
+        CreateButton,
+        CreateEmbed,
+        CreateEmbedFooter,
+        CreateInteractionResponse,
+        CreateInteractionResponseMessage,
+        CreateMessage,
+        EditMessage,
+        Member,
+        Mentionable,
+        Message,
+        UserId,
+    },
+    CreateReply,
+};
+
+use crate::{
+    checks::{is_game_moderator, needs_active_game},
+    commands::{
+        finale::{format_finale_score, get_finale_winner_embed},
+        vote::get_game_finished_embed,
+        GameChannel,
+    },
+    embed_color,
+    engine::{Command, Event, GameEngine, Phase},
+    game::{AnswerStats, Finale, Game},
+    guild_locale,
+    guild_session,
+    i18n::{tr, Locale, Localize},
+    questions::{Question, QuestionBank},
+    t,
+    CmdRet,
+    Context,
+    Error,
+};
+
+async fn autocomplete_category(ctx: Context<'_>, partial: &str) -> Vec<String> {
+    let partial = partial.to_lowercase();
+
+    ctx.data()
+        .questions
+        .categories()
+        .into_iter()
+        .filter(|category| category.to_lowercase().contains(&partial))
+        .map(str::to_owned)
+        .collect()
+}
+
+#[command(slash_command, guild_only, check = needs_active_game, check = is_game_moderator)]
+pub async fn ask(
+    ctx: Context<'_>,
+    #[rename = "user"] member: Member,
+    #[rename = "kategorie"]
+    #[autocomplete = "autocomplete_category"]
+    category: Option<String>,
+) -> CmdRet {
+    let locale = guild_locale(ctx).await;
+    let drawn = {
+        let session = guild_session(ctx).await;
+        let mut lock = session.engine.lock().await;
+        let engine = lock.as_mut().unwrap();
+
+        draw_question(
+            engine,
+            &ctx.data().questions,
+            member.user.id,
+            category.as_deref(),
+            locale,
+        )?
+    };
+
+    let embed = get_question_embed(
+        &member,
+        &drawn.question,
+        drawn.round,
+        drawn.in_finale,
+        locale,
+    );
+    let prefix = format!("{}_answer_", ctx.id());
+
+    let message = ctx
+        .send(
+            CreateReply::default()
+                .embed(embed.clone())
+                .components(get_judge_buttons(&prefix, locale)),
+        )
+        .await?
+        .into_message()
+        .await?;
+
+    ctx.send(
+        CreateReply::default()
+            .embed(get_answer_embed(&drawn.question, locale))
+            .ephemeral(true),
+    )
+    .await?;
+
+    collect_judgement(
+        ctx.into(),
+        ctx.author().id,
+        message,
+        embed,
+        &prefix,
+        drawn.index,
+    )
+    .await
+}
+
+/// A question that was just asked
+#[derive(Debug, Clone)]
+pub struct DrawnQuestion {
+    // the index in [Game::asked_questions]
+    pub index: usize,
+    pub round: u32,
+    pub question: Question,
+    pub in_finale: bool,
+}
+
+/// Draws a question the game hasn't had yet and asks it to the player
+pub fn draw_question(
+    engine: &mut GameEngine,
+    questions: &QuestionBank,
+    player: UserId,
+    category: Option<&str>,
+    locale: Locale,
+) -> Result<DrawnQuestion, Error> {
+    let asked = engine
+        .game()
+        .asked_questions
+        .iter()
+        .map(|asked| &asked.question);
+    let Some(question) = questions.draw(asked, category) else {
+        return Err(match category {
+            Some(category) => t!(locale, "question.no_questions_in", category = category),
+            None => t!(locale, "question.no_questions"),
+        }
+        .into());
+    };
+
+    let events = engine.handle(Command::AskQuestion {
+        player,
+        question: question.clone(),
+    })?;
+
+    let (round, question) = events
+        .into_iter()
+        .find_map(|event| match event {
+            Event::QuestionAsked {
+                round, question, ..
+            } => Some((round, question)),
+            _ => None,
+        })
+        .expect("Expected the question to be asked");
+
+    Ok(DrawnQuestion {
+        index: engine.game().asked_questions.len() - 1,
+        round,
+        question,
+        in_finale: engine.phase() == Phase::Finale,
+    })
+}
+
+pub fn get_judge_buttons(prefix: &str, locale: Locale) -> Vec<CreateActionRow> {
+    vec![CreateActionRow::Buttons(vec![
+        CreateButton::new(format!("{prefix}correct"))
+            .label(t!(locale, "question.correct"))
+            .style(ButtonStyle::Success),
+        CreateButton::new(format!("{prefix}wrong"))
+            .label(t!(locale, "question.wrong"))
+            .style(ButtonStyle::Danger),
+    ])]
+}
+
+pub fn get_question_embed(
+    member: &Member,
+    question: &Question,
+    round: u32,
+    in_finale: bool,
+    locale: Locale,
+) -> CreateEmbed {
+    let footer = if in_finale {
+        t!(locale, "finale.title")
+    } else {
+        t!(locale, "question.round", round = round)
+    };
+
+    CreateEmbed::default()
+        .title(t!(locale, "question.title", player = member.display_name()))
+        .description(format!("❓ {}\n\n{}", member.mention(), question.question))
+        .field(t!(locale, "question.category"), &question.category, true)
+        .footer(CreateEmbedFooter::new(footer))
+        .color(embed_color())
+}
+
+/// What the moderator decides about an answer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum Verdict {
+    #[name = "Richtig"]
+    Correct,
+    #[name = "Falsch"]
+    Wrong,
+    #[name = "Überspringen"]
+    Skip,
+}
+
+impl Localize for Verdict {
+    fn localize(&self, locale: Locale) -> String {
+        t!(
+            locale,
+            match self {
+                Verdict::Correct => "choices.verdict.correct",
+                Verdict::Wrong => "choices.verdict.wrong",
+                Verdict::Skip => "choices.verdict.skip",
+            }
+        )
+    }
+}
+
+impl Verdict {
+    fn command(self, question: usize) -> Command {
+        match self {
+            Verdict::Correct => Command::JudgeAnswer {
+                question,
+                correct: true,
+            },
+            Verdict::Wrong => Command::JudgeAnswer {
+                question,
+                correct: false,
+            },
+            Verdict::Skip => Command::SkipQuestion { question },
+        }
+    }
+
+    fn describe(self, locale: Locale) -> String {
+        match self {
+            Verdict::Correct => format!("✅ {}", tr(locale, "question.correct")),
+            Verdict::Wrong => format!("❌ {}", tr(locale, "question.wrong")),
+            Verdict::Skip => format!("⏭️ {}", tr(locale, "question.skipped")),
+        }
+    }
+}
+
+// for an open question whose buttons timed out or stopped working with a restart of the bot
+#[command(slash_command, guild_only, check = needs_active_game, check = is_game_moderator)]
+pub async fn judge(ctx: Context<'_>, #[rename = "urteil"] verdict: Verdict) -> CmdRet {
+    let locale = guild_locale(ctx).await;
+    let index = {
+        let session = guild_session(ctx).await;
+        let lock = session.engine.lock().await;
+        lock.as_ref().and_then(GameEngine::open_question)
+    }
+    .ok_or_else(|| t!(locale, "question.none_open"))?;
+
+    let (events, finale) = judge_question(ctx.into(), index, verdict).await?;
+
+    let embed = CreateEmbed::default()
+        .title(t!(locale, "question.judged"))
+        .color(embed_color());
+    let (embed, winner_embeds) =
+        get_verdict_embeds(embed, verdict, &events, finale.as_ref(), locale);
+
+    ctx.send(CreateReply {
+        embeds: [embed].into_iter().chain(winner_embeds).collect(),
+        ..Default::default()
+    })
+    .await?;
+
+    Ok(())
+}
+
+/// Waits for the moderator to press "Richtig" or "Falsch" on the question. Without an answer in
+/// time the question is skipped, so the finale doesn't get stuck on it.
+pub async fn collect_judgement(
+    channel: GameChannel<'_>,
+    moderator: UserId,
+    mut message: Message,
+    embed: CreateEmbed,
+    prefix: &str,
+    index: usize,
+) -> CmdRet {
+    let mut collector = ComponentInteractionCollector::new(channel.serenity)
+        .message_id(message.id)
+        .author_id(moderator)
+        .timeout(
+            channel
+                .data
+                .game_config(channel.guild_id)
+                .await
+                .answer_timeout(),
+        )
+        .stream();
+
+    let locale = channel.session().await.locale().await;
+
+    let Some(press) = collector.next().await else {
+        // `/judge` might have been faster
+        let edit = match judge_question(channel, index, Verdict::Skip).await {
+            Ok(_) => EditMessage::new().embed(embed.field(
+                t!(locale, "question.answer"),
+                Verdict::Skip.describe(locale),
+                true,
+            )),
+            Err(_) => EditMessage::new(),
+        };
+        message
+            .edit(channel.serenity, edit.components(vec![]))
+            .await?;
+        return Ok(());
+    };
+
+    let verdict = if press.data.custom_id == format!("{prefix}correct") {
+        Verdict::Correct
+    } else {
+        Verdict::Wrong
+    };
+
+    let mut winner_embeds = Vec::new();
+    let embed = match judge_question(channel, index, verdict).await {
+        Ok((events, finale)) => {
+            let (embed, winners) =
+                get_verdict_embeds(embed, verdict, &events, finale.as_ref(), locale);
+            winner_embeds = winners;
+            embed
+        },
+        Err(err) => CreateEmbed::default()
+            .title(t!(locale, "errors.title"))
+            .description(err)
+            .color(Color::DARK_RED),
+    };
+
+    press
+        .create_response(
+            channel.serenity,
+            CreateInteractionResponse::UpdateMessage(
+                CreateInteractionResponseMessage::new()
+                    .embeds(vec![embed])
+                    .components(vec![]),
+            ),
+        )
+        .await?;
+
+    if !winner_embeds.is_empty() {
+        channel
+            .channel_id
+            .send_message(channel.serenity, CreateMessage::new().embeds(winner_embeds))
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Hands the verdict to the engine. Returns the events and the finale afterwards, or the error in
+/// the language of the guild.
+async fn judge_question(
+    channel: GameChannel<'_>,
+    index: usize,
+    verdict: Verdict,
+) -> Result<(Vec<Event>, Option<Finale>), String> {
+    let session = channel.session().await;
+    let locale = session.locale().await;
+    let result = {
+        let mut lock = session.engine.lock().await;
+        match lock.as_mut() {
+            Some(engine) => match engine.handle(verdict.command(index)) {
+                Ok(events) => {
+                    session.record_finished_game(engine, &events).await;
+                    Ok((events, engine.game().finale.clone()))
+                },
+                Err(err) => Err(err.localize(locale)),
+            },
+            None => Err(t!(locale, "checks.no_active_game")),
+        }
+    };
+
+    // the buttons keep running until the answer is judged, so `post_command` would be too late
+    if result.is_ok() {
+        channel.data.persist_or_log().await;
+    }
+
+    result
+}
+
+/// Adds the verdict (and the score of the finale) to the embed. The winner of the finale is
+/// announced in embeds of their own.
+fn get_verdict_embeds(
+    embed: CreateEmbed,
+    verdict: Verdict,
+    events: &[Event],
+    finale: Option<&Finale>,
+    locale: Locale,
+) -> (CreateEmbed, Vec<CreateEmbed>) {
+    let embed = embed.field(
+        t!(locale, "question.answer"),
+        verdict.describe(locale),
+        true,
+    );
+    let Some(finale) = finale else {
+        return (embed, Vec::new());
+    };
+
+    let mut winner_embeds = Vec::new();
+    for event in events {
+        if let Event::GameFinished {
+            winner: Some(winner),
+            standings,
+        } = event
+        {
+            winner_embeds.push(get_finale_winner_embed(finale, *winner, locale));
+            winner_embeds.push(get_game_finished_embed(Some(*winner), standings, locale));
+        }
+    }
+
+    (
+        embed.field(
+            t!(locale, "finale.title"),
+            format_finale_score(finale),
+            false,
+        ),
+        winner_embeds,
+    )
+}
+
+/// Only meant for the moderator
+pub fn get_answer_embed(question: &Question, locale: Locale) -> CreateEmbed {
+    let mut embed = CreateEmbed::default()
+        .title(t!(locale, "question.answer"))
+        .description(format!("✅ {}", question.answer))
+        .color(embed_color());
+
+    if !question.alternatives.is_empty() {
+        embed = embed.field(
+            t!(locale, "question.alternatives"),
+            question.alternatives.join("\n"),
+            false,
+        );
+    }
+
+    embed
+}
+
+pub fn format_answer_stats(stats: &AnswerStats) -> String {
+    format!(
+        "✅ {} · ❌ {} · ⏭️ {}",
+        stats.correct, stats.wrong, stats.skipped
+    )
+}
+
+/// The answers of every living player in the current round, the worst players first.
+///
+/// Returns `None` if no question was asked in this round.
+pub fn get_round_summary_embed(game: &Game, locale: Locale) -> Option<CreateEmbed> {
+    let mut players = game
+        .members
+        .iter()
+        .filter(|(_, lives)| **lives > 0)
+        .map(|(player, _)| (*player, game.answer_stats(*player, Some(game.round))))
+        .collect::<Vec<_>>();
+
+    if players.iter().all(|(_, stats)| stats.asked == 0) {
+        return None;
+    }
+
+    players.sort_by_key(|(player, stats)| (std::cmp::Reverse(stats.wrong), stats.correct, *player));
+
+    let description = players
+        .iter()
+        .map(|(player, stats)| format!("{}: {}", player.mention(), format_answer_stats(stats)))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    Some(
+        CreateEmbed::default()
+            .title(t!(locale, "question.round_summary", round = game.round))
+            .description(description)
+            .color(embed_color()),
+    )
+}
@@ -0,0 +1,68 @@
+use poise::{command, serenity_prelude::CreateEmbed, CreateReply};
+
+use crate::{
+    checks::{is_game_moderator, needs_active_game},
+    embed_color,
+    engine::Command,
+    guild_locale,
+    guild_session,
+    i18n::{tr, Locale},
+    rules::VoteRules,
+    t,
+    CmdRet,
+    Context,
+};
+
+// every rule that isn't given stays as it is
+#[command(slash_command, rename = "set-vote-rules", guild_only, check = needs_active_game, check = is_game_moderator)]
+pub async fn set_vote_rules(
+    ctx: Context<'_>,
+    #[rename = "selbst"] allow_self_vote: Option<bool>,
+    #[rename = "wenigste-leben"] allow_fewest_lives: Option<bool>,
+    #[rename = "zweimal"] allow_same_target_twice: Option<bool>,
+    #[rename = "moderator"] allow_moderator: Option<bool>,
+) -> CmdRet {
+    let vote_rules = {
+        let session = guild_session(ctx).await;
+        let mut lock = session.engine.lock().await;
+        let engine = lock.as_mut().unwrap();
+
+        let current = engine.game().vote_rules;
+        let vote_rules = VoteRules {
+            allow_self_vote: allow_self_vote.unwrap_or(current.allow_self_vote),
+            allow_fewest_lives: allow_fewest_lives.unwrap_or(current.allow_fewest_lives),
+            allow_same_target_twice: allow_same_target_twice
+                .unwrap_or(current.allow_same_target_twice),
+            allow_moderator: allow_moderator.unwrap_or(current.allow_moderator),
+        };
+
+        engine.handle(Command::SetVoteRules { vote_rules })?;
+        vote_rules
+    };
+
+    let locale = guild_locale(ctx).await;
+    let embed = CreateEmbed::default()
+        .title(t!(locale, "rules.title"))
+        .description(describe_vote_rules(&vote_rules, locale))
+        .color(embed_color());
+
+    ctx.send(CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
+
+fn describe_vote_rules(vote_rules: &VoteRules, locale: Locale) -> String {
+    let rule = |allowed: bool, key: &str| {
+        format!("{} {}", if allowed { "✅" } else { "❌" }, tr(locale, key))
+    };
+
+    [
+        rule(vote_rules.allow_self_vote, "rules.self_vote"),
+        rule(vote_rules.allow_fewest_lives, "rules.fewest_lives"),
+        rule(
+            vote_rules.allow_same_target_twice,
+            "rules.same_target_twice",
+        ),
+        rule(vote_rules.allow_moderator, "rules.moderator"),
+    ]
+    .join("\n")
+}
@@ -1,33 +1,62 @@
 use poise::{
     command,
-    serenity_prelude::{CreateEmbed, Member, Mentionable},
+    serenity_prelude::{CreateEmbed, Member, Mentionable, UserId},
     CreateReply,
 };
 
 use crate::{
     checks::{is_game_moderator, needs_active_game},
+    commands::vote::get_resolution_embeds,
+    embed_color,
+    engine::{Command, Event},
+    guild_locale,
+    guild_session,
+    i18n::Locale,
+    t,
     CmdRet,
     Context,
-    DEFAULT_COLOR,
 };
 
 #[command(slash_command, rename = "set-lives", guild_only, check = needs_active_game, check = is_game_moderator)]
 pub async fn set_lives(ctx: Context<'_>, #[rename = "user"] member: Member, amount: i32) -> CmdRet {
-    let mut lock = ctx.data().game.lock().await;
-    let game = lock.as_mut().unwrap();
+    let locale = guild_locale(ctx).await;
+    let session = guild_session(ctx).await;
+    let mut lock = session.engine.lock().await;
+    let engine = lock.as_mut().unwrap();
 
-    game.set_player_health(ctx.author().id, amount)?;
+    let events = engine.handle(Command::SetLives {
+        player: member.user.id,
+        lives: amount,
+    })?;
+    session.record_finished_game(engine, &events).await;
 
+    ctx.send(CreateReply {
+        embeds: get_lives_changed_embeds(member.user.id, amount, &events, locale),
+        ..Default::default()
+    })
+    .await?;
+
+    Ok(())
+}
+
+pub fn get_lives_changed_embeds(
+    player: UserId,
+    lives: i32,
+    events: &[Event],
+    locale: Locale,
+) -> Vec<CreateEmbed> {
     let embed = CreateEmbed::default()
-        .title("User wurde geupdated.")
-        .description(format!(
-            ":pencil2: {} hat nun `{}` Leben.",
-            member.mention(),
-            amount
+        .title(t!(locale, "set_lives.title"))
+        .description(t!(
+            locale,
+            "set_lives.description",
+            player = player.mention(),
+            lives = lives
         ))
-        .color(DEFAULT_COLOR);
-
-    ctx.send(CreateReply::default().embed(embed)).await?;
+        .color(embed_color());
 
-    Ok(())
+    // the player might have been eliminated, which can end the game
+    let mut embeds = vec![embed];
+    embeds.extend(get_resolution_embeds(events, locale));
+    embeds
 }
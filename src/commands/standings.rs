@@ -0,0 +1,72 @@
+use poise::{
+    command,
+    serenity_prelude::{CreateEmbed, Mentionable},
+    CreateReply,
+};
+
+use crate::{
+    checks::needs_active_game,
+    embed_color,
+    engine::Phase,
+    game::Standing,
+    guild_locale,
+    guild_session,
+    i18n::Locale,
+    t,
+    CmdRet,
+    Context,
+};
+
+#[command(slash_command, guild_only, check = needs_active_game)]
+pub async fn standings(ctx: Context<'_>) -> CmdRet {
+    let (standings, finished) = {
+        let session = guild_session(ctx).await;
+        let lock = session.engine.lock().await;
+        let engine = lock.as_ref().expect("Expected an active game");
+
+        (engine.game().standings(), engine.phase() == Phase::Finished)
+    };
+
+    let locale = guild_locale(ctx).await;
+    let embed = if standings.is_empty() {
+        CreateEmbed::default()
+            .title(t!(locale, "standings.title"))
+            .description(t!(locale, "game.no_players"))
+            .color(embed_color())
+    } else {
+        CreateEmbed::default()
+            .title(if finished {
+                t!(locale, "standings.final")
+            } else {
+                t!(locale, "standings.current")
+            })
+            .description(format_standings(&standings, locale))
+            .color(embed_color())
+    };
+
+    ctx.send(CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
+
+/// One line per player, the first three places get a medal
+pub fn format_standings(standings: &[Standing], locale: Locale) -> String {
+    standings
+        .iter()
+        .map(|standing| {
+            let place = match standing.place {
+                1 => "🥇".to_owned(),
+                2 => "🥈".to_owned(),
+                3 => "🥉".to_owned(),
+                place => format!("{place}."),
+            };
+            let status = match standing.eliminated_in {
+                Some(round) => t!(locale, "standings.eliminated_in", round = round),
+                None if standing.lives > 0 => format!("{} ❤", standing.lives),
+                None => t!(locale, "standings.eliminated"),
+            };
+
+            format!("{place} {} · {status}", standing.player.mention())
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,116 @@
+use poise::{
+    command,
+    serenity_prelude::{CreateEmbed, Member, Mentionable},
+    CreateReply,
+};
+
+use crate::{
+    embed_color,
+    guild_locale,
+    guild_session,
+    i18n::Locale,
+    stats::PlayerStats,
+    t,
+    CmdRet,
+    Context,
+};
+
+/// How many players `/leaderboard` shows
+const LEADERBOARD_SIZE: usize = 10;
+
+#[command(slash_command, guild_only)]
+pub async fn stats(ctx: Context<'_>, #[rename = "user"] member: Option<Member>) -> CmdRet {
+    let locale = guild_locale(ctx).await;
+    let player = member.map_or(ctx.author().id, |member| member.user.id);
+    let stats = guild_session(ctx).await.stats.lock().await.get(player);
+
+    let embed = if stats.games_played == 0 {
+        CreateEmbed::default()
+            .title(t!(locale, "stats.title"))
+            .description(t!(locale, "stats.no_games", player = player.mention()))
+            .color(embed_color())
+    } else {
+        CreateEmbed::default()
+            .title(t!(locale, "stats.title"))
+            .description(player.mention().to_string())
+            .field(
+                t!(locale, "stats.games"),
+                stats.games_played.to_string(),
+                true,
+            )
+            .field(t!(locale, "stats.wins"), stats.wins.to_string(), true)
+            .field(
+                t!(locale, "stats.place"),
+                format_places(&stats, locale),
+                true,
+            )
+            .field(
+                t!(locale, "stats.votes_received"),
+                stats.votes_received.to_string(),
+                true,
+            )
+            .field(
+                t!(locale, "stats.votes_cast"),
+                stats.votes_cast.to_string(),
+                true,
+            )
+            .field(
+                t!(locale, "stats.voted_out"),
+                stats.times_voted_out.to_string(),
+                true,
+            )
+            .color(embed_color())
+    };
+
+    ctx.send(CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
+
+#[command(slash_command, guild_only)]
+pub async fn leaderboard(ctx: Context<'_>) -> CmdRet {
+    let locale = guild_locale(ctx).await;
+    let leaderboard = guild_session(ctx).await.stats.lock().await.leaderboard();
+
+    let description = if leaderboard.is_empty() {
+        t!(locale, "leaderboard.empty")
+    } else {
+        leaderboard
+            .iter()
+            .take(LEADERBOARD_SIZE)
+            .enumerate()
+            .map(|(index, (player, stats))| {
+                t!(
+                    locale,
+                    "leaderboard.entry",
+                    place = index + 1,
+                    player = player.mention(),
+                    wins = stats.wins,
+                    games = stats.games_played,
+                    places = format_places(stats, locale)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let embed = CreateEmbed::default()
+        .title(t!(locale, "leaderboard.title"))
+        .description(description)
+        .color(embed_color());
+
+    ctx.send(CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
+
+/// e.g. "Ø 2.5 (bester: 1.)"
+fn format_places(stats: &PlayerStats, locale: Locale) -> String {
+    match (stats.average_place(), stats.best_place) {
+        (Some(average), Some(best)) => t!(
+            locale,
+            "stats.places",
+            average = format!("{average:.1}"),
+            best = best
+        ),
+        _ => "-".to_owned(),
+    }
+}
@@ -0,0 +1,204 @@
+use poise::{
+    command,
+    serenity_prelude::{
+        futures::StreamExt,
+        Color,
+        ComponentInteractionCollector,
+        CreateEmbed,
+        CreateInteractionResponse,
+        CreateInteractionResponseMessage,
+        CreateMessage,
+        EditMessage,
+        Member,
+        Mentionable,
+        UserId,
+    },
+    CreateReply,
+};
+
+use crate::{
+    checks::{is_game_moderator, needs_active_game},
+    commands::{
+        vote::{get_player_buttons, get_resolution_embeds, MAX_VOTE_BUTTONS},
+        GameChannel,
+    },
+    embed_color,
+    engine::Command,
+    game::TieBreak,
+    guild_locale,
+    guild_session,
+    i18n::{localize_error, Localize},
+    t,
+    CmdRet,
+    Context,
+    Error,
+};
+
+#[command(slash_command, rename = "set-tie-break", guild_only, check = needs_active_game, check = is_game_moderator)]
+pub async fn set_tie_break(ctx: Context<'_>, #[rename = "regel"] tie_break: TieBreak) -> CmdRet {
+    {
+        let session = guild_session(ctx).await;
+        let mut lock = session.engine.lock().await;
+        let engine = lock.as_mut().unwrap();
+
+        engine.handle(Command::SetTieBreak { tie_break })?;
+    }
+
+    let locale = guild_locale(ctx).await;
+    let embed = CreateEmbed::default()
+        .title(t!(locale, "tie_break.title"))
+        .description(t!(
+            locale,
+            "tie_break.changed",
+            rule = tie_break.localize(locale)
+        ))
+        .color(embed_color());
+
+    ctx.send(CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
+
+#[command(slash_command, rename = "decide-tie", guild_only, check = needs_active_game, check = is_game_moderator)]
+pub async fn decide_tie(ctx: Context<'_>, #[rename = "user"] member: Member) -> CmdRet {
+    let events = {
+        let session = guild_session(ctx).await;
+        let mut lock = session.engine.lock().await;
+        let engine = lock.as_mut().unwrap();
+
+        let events = engine.handle(Command::DecideTie {
+            loser: member.user.id,
+        })?;
+        session.record_finished_game(engine, &events).await;
+        events
+    };
+
+    let reply = CreateReply {
+        embeds: get_resolution_embeds(&events, guild_locale(ctx).await),
+        ..Default::default()
+    };
+    ctx.send(reply).await?;
+
+    Ok(())
+}
+
+/// Lets the moderator pick the player that loses a life via buttons
+pub async fn prompt_tie_decision(channel: GameChannel<'_>, candidates: &[UserId]) -> CmdRet {
+    let session = channel.session().await;
+    let locale = session.locale().await;
+    let moderator = {
+        let lock = session.engine.lock().await;
+        lock.as_ref()
+            .expect("Expected an active game")
+            .game()
+            .moderator
+    };
+
+    let embed = CreateEmbed::default()
+        .title(t!(locale, "tie_break.tie"))
+        .color(embed_color());
+
+    // too many players for the buttons, which leaves `/decide-tie`
+    if candidates.len() > MAX_VOTE_BUTTONS {
+        channel
+            .channel_id
+            .send_message(
+                channel.serenity,
+                CreateMessage::new()
+                    .content(moderator.mention().to_string())
+                    .embed(embed.description(t!(locale, "tie_break.no_decision"))),
+            )
+            .await?;
+        return Ok(());
+    }
+
+    let prefix = format!("{}_tie_", channel.id);
+    let buttons = get_player_buttons(channel, &prefix, candidates).await?;
+
+    let mut message = channel
+        .channel_id
+        .send_message(
+            channel.serenity,
+            CreateMessage::new()
+                .content(moderator.mention().to_string())
+                .embed(embed.description(t!(locale, "tie_break.who_loses")))
+                .components(buttons),
+        )
+        .await?;
+
+    let mut collector = ComponentInteractionCollector::new(channel.serenity)
+        .message_id(message.id)
+        .author_id(moderator)
+        // `/decide-tie` works after that as well
+        .timeout(
+            channel
+                .data
+                .game_config(channel.guild_id)
+                .await
+                .tie_decision_timeout(),
+        )
+        .stream();
+
+    let Some(press) = collector.next().await else {
+        message
+            .edit(
+                channel.serenity,
+                EditMessage::new()
+                    .embed(
+                        CreateEmbed::default()
+                            .title(t!(locale, "tie_break.tie"))
+                            .description(t!(locale, "tie_break.no_decision"))
+                            .color(Color::RED),
+                    )
+                    .components(vec![]),
+            )
+            .await?;
+
+        return Ok(());
+    };
+
+    let loser = press
+        .data
+        .custom_id
+        .strip_prefix(&prefix)
+        .and_then(|id| id.parse().ok())
+        .map(UserId::new)
+        .expect("Expected a tie-break button");
+
+    let result = {
+        let mut lock = session.engine.lock().await;
+        match lock.as_mut() {
+            Some(engine) => match engine.handle(Command::DecideTie { loser }) {
+                Ok(events) => {
+                    session.record_finished_game(engine, &events).await;
+                    Ok(events)
+                },
+                Err(err) => Err(Error::from(err)),
+            },
+            None => Err(Error::from(t!(locale, "checks.no_active_game"))),
+        }
+    };
+
+    let embeds = match result {
+        Ok(events) => {
+            channel.data.persist_or_log().await;
+            get_resolution_embeds(&events, locale)
+        },
+        Err(err) => vec![CreateEmbed::default()
+            .title(t!(locale, "errors.title"))
+            .description(localize_error(err.as_ref(), locale))
+            .color(Color::DARK_RED)],
+    };
+
+    press
+        .create_response(
+            channel.serenity,
+            CreateInteractionResponse::UpdateMessage(
+                CreateInteractionResponseMessage::new()
+                    .embeds(embeds)
+                    .components(vec![]),
+            ),
+        )
+        .await?;
+
+    Ok(())
+}
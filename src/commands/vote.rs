@@ -1,5 +1,6 @@
 use std::{collections::HashMap, time::Duration};
 
+use chrono::{DateTime, TimeDelta, Utc};
 use itertools::Itertools;
 use poise::{
     command,
@@ -12,10 +13,14 @@ use poise::{
         CreateActionRow,
         CreateButton,
         CreateEmbed,
+        CreateEmbedFooter,
         CreateInteractionResponse,
         CreateInteractionResponseMessage,
+        CreateMessage,
+        EditMessage,
         Member,
         Mentionable,
+        Message,
         UserId,
     },
     CreateReply,
@@ -23,33 +28,76 @@ use poise::{
 
 use crate::{
     checks::{
-        author_is_alive,
+        author_can_vote,
         did_not_vote,
         is_game_moderator,
         is_in_game,
         needs_active_game,
         needs_active_voting,
     },
-    game::Voting,
+    commands::{
+        finale::get_finale_started_embed,
+        question::get_round_summary_embed,
+        standings::format_standings,
+        tie_break::prompt_tie_decision,
+        GameChannel,
+    },
+    embed_color,
+    engine::{Command, EngineError, Event, GameEngine, VoteOutcome},
+    game::{RoundRecord, Standing, TieBreak, VoteChange, VoteChangePolicy, VoteChanges},
+    guild_config,
+    guild_locale,
+    guild_session,
+    i18n::{localize_error, tr, Locale, Localize},
+    t,
     CmdRet,
     Context,
     Error,
-    DEFAULT_COLOR,
 };
 
-type MemberVoteCount = HashMap<UserId, i32>;
+/// How often the voting message is refreshed, so votes cast via `/vote` show up as well
+const VOTING_MESSAGE_REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Players that haven't voted yet get pinged once the remaining time drops below this
+const VOTING_REMINDER_BEFORE_DEADLINE: TimeDelta = TimeDelta::seconds(30);
+
+// Discord allows at most 5 rows with 5 buttons each
+const MAX_BUTTONS_PER_ROW: usize = 5;
+pub const MAX_VOTE_BUTTONS: usize = 25;
 
 #[command(slash_command, rename = "start-voting", guild_only, check = needs_active_game, check = is_game_moderator)]
-pub async fn start_voting(ctx: Context<'_>) -> CmdRet {
+pub async fn start_voting(
+    ctx: Context<'_>,
+    #[rename = "dauer"]
+    #[min = 10]
+    #[max = 3600]
+    duration: Option<u32>,
+    #[rename = "anonym"] anonymous: Option<bool>,
+) -> CmdRet {
     let creator = ctx.interaction.member.as_ref().unwrap();
-    if ctx.data().voting.lock().await.is_some() {
-        prompt_override_vote(ctx, creator).await
+    let anonymous = anonymous.unwrap_or(false);
+    let voting_is_active = guild_session(ctx)
+        .await
+        .engine
+        .lock()
+        .await
+        .as_ref()
+        .is_some_and(|engine| engine.voting().is_some());
+
+    if voting_is_active {
+        prompt_override_vote(ctx, creator, duration, anonymous).await
     } else {
-        create_new_vote(ctx, creator, None).await
+        create_new_vote(ctx, creator, duration, anonymous, None).await
     }
 }
 
-pub async fn prompt_override_vote(ctx: Context<'_>, creator: &Member) -> CmdRet {
+pub async fn prompt_override_vote(
+    ctx: Context<'_>,
+    creator: &Member,
+    duration: Option<u32>,
+    anonymous: bool,
+) -> CmdRet {
+    let locale = guild_locale(ctx).await;
     let ctx_id = ctx.id().to_string();
 
     let yes_no_id = (format!("{ctx_id}_yes"), format!("{ctx_id}_no"));
@@ -58,15 +106,15 @@ pub async fn prompt_override_vote(ctx: Context<'_>, creator: &Member) -> CmdRet
         CreateReply::default()
             .embed(
                 CreateEmbed::default()
-                    .description("Es gibt ein laufendes Voting.\nMöchtest du es überschreiben?")
+                    .description(t!(locale, "voting.override"))
                     .color(Color::RED),
             )
             .components(vec![CreateActionRow::Buttons(vec![
                 CreateButton::new(&yes_no_id.1)
-                    .label("Nein")
+                    .label(t!(locale, "game.no"))
                     .style(ButtonStyle::Danger),
                 CreateButton::new(&yes_no_id.0)
-                    .label("Ja")
+                    .label(t!(locale, "game.yes"))
                     .style(ButtonStyle::Success),
             ])]),
     )
@@ -74,13 +122,15 @@ pub async fn prompt_override_vote(ctx: Context<'_>, creator: &Member) -> CmdRet
 
     let mut collector = ComponentInteractionCollector::new(ctx.serenity_context())
         .author_id(ctx.author().id)
-        .timeout(Duration::from_secs(60))
+        .timeout(guild_config(ctx).await.confirm_timeout())
         .filter(move |interaction| interaction.data.custom_id.starts_with(&ctx_id))
         .stream();
 
     if let Some(press) = collector.next().await {
         match &press.data.custom_id {
-            id if id == &yes_no_id.0 => create_new_vote(ctx, creator, Some(press)).await?,
+            id if id == &yes_no_id.0 => {
+                create_new_vote(ctx, creator, duration, anonymous, Some(press)).await?
+            },
             _ => {
                 press
                     .create_response(
@@ -89,7 +139,7 @@ pub async fn prompt_override_vote(ctx: Context<'_>, creator: &Member) -> CmdRet
                             CreateInteractionResponseMessage::new()
                                 .embed(
                                     CreateEmbed::default()
-                                        .description("Abgebrochen")
+                                        .description(t!(locale, "game.cancelled"))
                                         .color(Color::RED),
                                 )
                                 .components(vec![]),
@@ -106,214 +156,1252 @@ pub async fn prompt_override_vote(ctx: Context<'_>, creator: &Member) -> CmdRet
 pub async fn create_new_vote(
     ctx: Context<'_>,
     creator: &Member,
+    duration: Option<u32>,
+    anonymous: bool,
     edit_on: Option<ComponentInteraction>,
 ) -> CmdRet {
-    let mut active_voting = ctx.data().voting.lock().await;
+    let locale = guild_locale(ctx).await;
+    let (voting_id, summary) = {
+        let session = guild_session(ctx).await;
+        let mut lock = session.engine.lock().await;
+        let engine = lock.as_mut().expect("Expected an active game");
 
-    let embed = CreateEmbed::default()
-        .title("Vote gestartet")
-        .description("🕛 Das Voting wurde gestartet.\nMan kann absofort voten.")
-        .color(DEFAULT_COLOR);
+        begin_voting(engine, creator.user.id, duration, anonymous, locale)?
+    };
+
+    // this command keeps running until the voting is over, so `post_command` would be too late
+    ctx.data().persist_or_log().await;
+
+    if let Some(summary) = summary {
+        ctx.channel_id()
+            .send_message(ctx, CreateMessage::new().embed(summary))
+            .await?;
+    }
 
-    *active_voting = Some(Voting {
-        creator: creator.clone(),
-        map: HashMap::new(),
-    });
+    let (embed, buttons) = get_voting_message(ctx.into(), voting_id).await?;
 
-    if let Some(interaction) = edit_on {
+    let message = if let Some(interaction) = edit_on {
         interaction
             .create_response(
                 ctx,
                 CreateInteractionResponse::UpdateMessage(
                     CreateInteractionResponseMessage::new()
                         .embed(embed)
-                        .components(vec![]),
+                        .components(buttons),
                 ),
             )
             .await?;
+        interaction.get_response(ctx).await?
     } else {
-        ctx.send(CreateReply::default().embed(embed)).await?;
+        ctx.send(CreateReply::default().embed(embed).components(buttons))
+            .await?
+            .into_message()
+            .await?
+    };
+
+    drive_voting(ctx.into(), message, voting_id).await
+}
+
+/// Starts a voting, a running one is replaced. Returns the ID of the new voting and the summary
+/// of the question round before it.
+pub fn begin_voting(
+    engine: &mut GameEngine,
+    creator: UserId,
+    duration: Option<u32>,
+    anonymous: bool,
+    locale: Locale,
+) -> Result<(u32, Option<CreateEmbed>), EngineError> {
+    let deadline = duration.map(|seconds| Utc::now() + TimeDelta::seconds(seconds.into()));
+
+    // the override prompt was confirmed, or the control API was asked to override it
+    if engine.voting().is_some() {
+        engine.handle(Command::CancelVoting)?;
     }
 
-    Ok(())
+    engine.handle(Command::StartVoting {
+        creator,
+        deadline,
+        anonymous,
+    })?;
+
+    Ok((
+        engine.voting().expect("Voting was just started").id,
+        get_round_summary_embed(engine.game(), locale),
+    ))
 }
 
-#[command(slash_command, guild_only, check = needs_active_game, check = needs_active_voting, check = is_in_game, check = did_not_vote, check = author_is_alive)]
-pub async fn vote(
-    ctx: Context<'_>,
-    #[description = "Den User, den du voten willst"]
-    #[rename = "user"]
-    member: Member,
+/// Runs the voting message and every runoff that follows it
+pub async fn drive_voting(
+    channel: GameChannel<'_>,
+    mut message: Message,
+    mut voting_id: u32,
 ) -> CmdRet {
-    {
-        let mut active_voting = ctx.data().voting.lock().await;
-        let active_voting = active_voting.as_mut().unwrap();
+    loop {
+        let Some(events) = collect_button_votes(channel, voting_id, &mut message).await? else {
+            return Ok(());
+        };
+
+        match follow_up_resolution(channel, &events).await? {
+            Some((runoff_message, runoff_id)) => {
+                message = runoff_message;
+                voting_id = runoff_id;
+            },
+            None => return Ok(()),
+        }
+    }
+}
 
-        let mut lock = ctx.data().game.lock().await;
-        let game = lock.as_mut().unwrap();
+/// Posts a new message for a voting restored from the snapshot and runs it, as nothing listens to
+/// the buttons of the old one after a restart. An expired voting is closed right away.
+pub async fn resume_voting(channel: GameChannel<'_>) -> CmdRet {
+    let voting = {
+        let session = channel.session().await;
+        let lock = session.engine.lock().await;
+        lock.as_ref()
+            .and_then(|engine| engine.voting())
+            .map(|voting| (voting.id, voting.deadline))
+    };
+    let Some((voting_id, deadline)) = voting else {
+        return Ok(());
+    };
 
-        match game.members.get(&member.user.id) {
-            // user dead
-            Some(&hp) if (hp <= 0) => {
-                return Err(format!("❌ {} ist ausgeschieden.", member.mention()).into())
+    if deadline.is_some_and(|deadline| deadline <= Utc::now()) {
+        let Some(events) = close_voting_on_timeout(channel, voting_id).await? else {
+            return Ok(());
+        };
+
+        return match follow_up_resolution(channel, &events).await? {
+            Some((message, runoff_id)) => drive_voting(channel, message, runoff_id).await,
+            None => Ok(()),
+        };
+    }
+
+    let message = send_voting_message(channel, voting_id).await?;
+    drive_voting(channel, message, voting_id).await
+}
+
+/// Takes care of a tie that couldn't be broken right away.
+///
+/// Returns the message and ID of the runoff voting, if one was started.
+pub async fn follow_up_resolution(
+    channel: GameChannel<'_>,
+    events: &[Event],
+) -> Result<Option<(Message, u32)>, Error> {
+    for event in events {
+        match event {
+            Event::TieBreakPending { candidates } => {
+                prompt_tie_decision(channel, candidates).await?;
             },
-            // user not in game
-            None => return Err(format!("{} ist nicht im Spiel.", member.mention()).into()),
+            Event::RunoffStarted { .. } => {
+                let voting_id = {
+                    let session = channel.session().await;
+                    let lock = session.engine.lock().await;
+                    lock.as_ref()
+                        .and_then(|engine| engine.voting())
+                        .expect("Expected the runoff voting")
+                        .id
+                };
 
+                let message = send_voting_message(channel, voting_id).await?;
+                return Ok(Some((message, voting_id)));
+            },
             _ => (),
-        };
+        }
+    }
+
+    Ok(None)
+}
+
+/// Posts the message with the vote buttons into the channel of the game
+pub async fn send_voting_message(
+    channel: GameChannel<'_>,
+    voting_id: u32,
+) -> Result<Message, Error> {
+    let (embed, buttons) = get_voting_message(channel, voting_id).await?;
+
+    Ok(channel
+        .channel_id
+        .send_message(
+            channel.serenity,
+            CreateMessage::new().embed(embed).components(buttons),
+        )
+        .await?)
+}
+
+async fn get_voting_message(
+    channel: GameChannel<'_>,
+    voting_id: u32,
+) -> Result<(CreateEmbed, Vec<CreateActionRow>), Error> {
+    let Some(status) = get_voting_status(channel, voting_id).await else {
+        let locale = channel.session().await.locale().await;
+        return Err(t!(locale, "voting.already_ended").into());
+    };
+
+    // the embed tells the players to use `/vote` instead
+    let candidates = status.vote_targets();
+    let buttons = if candidates.len() > MAX_VOTE_BUTTONS {
+        Vec::new()
+    } else {
+        get_player_buttons(channel, &get_vote_prefix(channel, voting_id), &candidates).await?
+    };
 
-        active_voting.map.insert(ctx.author().id, member.user.id);
+    Ok((get_voting_embed(&status), buttons))
+}
+
+fn get_vote_prefix(channel: GameChannel<'_>, voting_id: u32) -> String {
+    format!("{}_{voting_id}_vote_", channel.id)
+}
+
+fn get_voting_embed(status: &VotingStatus) -> CreateEmbed {
+    let locale = status.locale;
+    let (title, mut description) = match &status.candidates {
+        Some(candidates) => (
+            t!(locale, "voting.runoff_title"),
+            t!(
+                locale,
+                "voting.runoff_started",
+                candidates = join_mentions(candidates)
+            ),
+        ),
+        None => (
+            t!(locale, "voting.started_title"),
+            t!(locale, "voting.started"),
+        ),
+    };
+
+    description.push_str("\n\n");
+    description.push_str(&t!(
+        locale,
+        "voting.progress",
+        voted = status.voted.len(),
+        players = status.voted.len() + status.not_voted.len()
+    ));
+
+    let policy = match status.vote_change_policy {
+        VoteChangePolicy::Locked => None,
+        VoteChangePolicy::Change => Some("voting.changes_allowed"),
+        VoteChangePolicy::ChangeOrWithdraw => Some("voting.changes_and_withdrawals_allowed"),
+    };
+    if let Some(policy) = policy {
+        description.push('\n');
+        description.push_str(tr(locale, policy));
     }
 
-    let embed = CreateEmbed::default()
-        .title("Voting")
-        .description(format!("✅ {} hat gevotet.", ctx.author().mention()))
-        .color(DEFAULT_COLOR);
+    if status.anonymous {
+        description.push('\n');
+        description.push_str(tr(locale, "voting.anonymous"));
+    }
 
-    ctx.send(CreateReply::default().embed(embed)).await?;
-    Ok(())
+    if status.vote_targets().len() > MAX_VOTE_BUTTONS {
+        description.push('\n');
+        description.push_str(&t!(locale, "voting.no_buttons", max = MAX_VOTE_BUTTONS));
+    }
+
+    if let Some(audience_votes) = status.audience_votes {
+        description.push('\n');
+        description.push_str(&t!(locale, "voting.audience", votes = audience_votes));
+    }
+
+    if let Some(deadline) = status.deadline {
+        // Discord renders this as a live countdown
+        description.push('\n');
+        description.push_str(&t!(
+            locale,
+            "voting.deadline",
+            timestamp = deadline.timestamp()
+        ));
+    }
+
+    CreateEmbed::default()
+        .title(title)
+        .description(description)
+        .color(embed_color())
 }
 
-enum VoteOutcome {
-    NoClearWinner {
-        members_with_equal_votes_count: i32,
-        max_vote_count: i32,
-    },
-    ClearWinner {
-        user: UserId,
-        num_votes: i32,
-    },
+/// One button per player, sorted by name. Fails if there are more players than fit on a message.
+pub async fn get_player_buttons(
+    channel: GameChannel<'_>,
+    custom_id_prefix: &str,
+    players: &[UserId],
+) -> Result<Vec<CreateActionRow>, Error> {
+    if players.len() > MAX_VOTE_BUTTONS {
+        let locale = channel.session().await.locale().await;
+        return Err(t!(locale, "voting.too_many_buttons", max = MAX_VOTE_BUTTONS).into());
+    }
+
+    let mut names = Vec::new();
+    for player in players {
+        let member = channel.guild_id.member(channel.serenity, player).await?;
+        names.push((member.display_name().to_owned(), *player));
+    }
+
+    let buttons = names
+        .into_iter()
+        .sorted()
+        .map(|(name, player)| {
+            CreateButton::new(format!("{custom_id_prefix}{player}"))
+                .label(name)
+                .style(ButtonStyle::Primary)
+        })
+        .collect::<Vec<_>>();
+
+    Ok(buttons
+        .chunks(MAX_BUTTONS_PER_ROW)
+        .map(|row| CreateActionRow::Buttons(row.to_vec()))
+        .collect())
 }
 
-#[command(slash_command, rename = "end-voting", guild_only, check = needs_active_voting, check = needs_active_game, check = is_game_moderator)]
-pub async fn end_voting(ctx: Context<'_>) -> CmdRet {
-    let mut voting = ctx.data().voting.lock().await;
+/// Records the button presses on the voting message until the voting is ended or overridden.
+///
+/// The message is refreshed regularly, so votes cast via `/vote` are counted as well. This is also
+/// where the timer of the voting runs out, in which case the events of the resolution are returned.
+async fn collect_button_votes(
+    channel: GameChannel<'_>,
+    voting_id: u32,
+    message: &mut Message,
+) -> Result<Option<Vec<Event>>, Error> {
+    let vote_prefix = get_vote_prefix(channel, voting_id);
+    let mut resolution = None;
 
-    let mut lock = ctx.data().game.lock().await;
-    let game = lock.as_mut().expect("Expected an active game");
+    let mut collector = ComponentInteractionCollector::new(channel.serenity)
+        .message_id(message.id)
+        .stream();
 
-    let (votes, mut who_voted_who_description) =
-        sum_up_votes(&voting.as_ref().unwrap().map).await?;
+    let mut shown_status = get_voting_status(channel, voting_id).await;
+    let mut reminded_for_deadline = None;
 
-    let mut member_died_embed: Option<CreateEmbed> = None;
+    loop {
+        let press =
+            match tokio::time::timeout(VOTING_MESSAGE_REFRESH_INTERVAL, collector.next()).await {
+                Ok(Some(press)) => Some(press),
+                Ok(None) => break,
+                // nobody pressed a button, but the message might still need a refresh
+                Err(_) => None,
+            };
 
-    match decide_winner(&votes) {
-        VoteOutcome::ClearWinner { user, num_votes } => {
-            // ...and remove 1 hp from them
-            game.members.entry(user).and_modify(|hp| *hp -= 1);
-            let member = ctx
-                .guild_id()
-                .expect("guild ID should be set")
-                .member(ctx, user)
-                .await?;
+        if let Some(press) = press {
+            handle_vote_button(channel, &press, &vote_prefix, voting_id).await?;
+        }
+
+        let Some(status) = get_voting_status(channel, voting_id).await else {
+            break;
+        };
+
+        if let Some(deadline) = status.deadline {
+            let now = Utc::now();
 
-            who_voted_who_description.push_str(&format!(
-                "**{member} hat mit `{num_votes}` die meisten votes und verliert ein Leben!**"
-            ));
+            if deadline <= now {
+                resolution = close_voting_on_timeout(channel, voting_id).await?;
+                break;
+            }
 
-            // check if the member that lost a life 'died' this round
-            if game.is_player_dead(user)? {
-                member_died_embed = Some(
+            if deadline - now <= VOTING_REMINDER_BEFORE_DEADLINE
+                && reminded_for_deadline != Some(deadline)
+                && !status.not_voted.is_empty()
+            {
+                send_voting_reminder(channel, deadline, &status.not_voted).await?;
+                reminded_for_deadline = Some(deadline);
+            }
+        }
+
+        if shown_status.as_ref() != Some(&status) {
+            message
+                .edit(
+                    channel.serenity,
+                    EditMessage::new().embed(get_voting_embed(&status)),
+                )
+                .await?;
+            shown_status = Some(status);
+        }
+    }
+
+    let locale = channel.session().await.locale().await;
+    message
+        .edit(
+            channel.serenity,
+            EditMessage::new()
+                .embed(
                     CreateEmbed::default()
-                        .description(format!("{member} ist ausgeschieden."))
-                        .color(DEFAULT_COLOR),
+                        .title(t!(locale, "voting.ended_title"))
+                        .description(t!(locale, "voting.already_ended"))
+                        .color(embed_color()),
                 )
-            }
+                .components(vec![]),
+        )
+        .await?;
+
+    Ok(resolution)
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct VotingStatus {
+    voted: Vec<UserId>,
+    not_voted: Vec<UserId>,
+    deadline: Option<DateTime<Utc>>,
+    candidates: Option<Vec<UserId>>,
+    // `None` if the audience can't vote
+    audience_votes: Option<usize>,
+    anonymous: bool,
+    vote_change_policy: VoteChangePolicy,
+    locale: Locale,
+}
+
+impl VotingStatus {
+    /// Everyone who is allowed to vote
+    fn players(&self) -> Vec<UserId> {
+        self.voted
+            .iter()
+            .chain(&self.not_voted)
+            .copied()
+            .sorted()
+            .collect()
+    }
+
+    /// Everyone who can be voted for
+    fn vote_targets(&self) -> Vec<UserId> {
+        self.candidates.clone().unwrap_or_else(|| self.players())
+    }
+}
+
+/// Returns `None` once the voting with the given ID is over
+async fn get_voting_status(channel: GameChannel<'_>, voting_id: u32) -> Option<VotingStatus> {
+    let session = channel.session().await;
+    let locale = session.locale().await;
+    let lock = session.engine.lock().await;
+    let engine = lock.as_ref()?;
+    let voting = engine.voting().filter(|voting| voting.id == voting_id)?;
+
+    let (mut voted, mut not_voted): (Vec<_>, Vec<_>) = engine
+        .living_players()
+        .partition(|player| voting.map.contains_key(player));
+    voted.sort();
+    not_voted.sort();
+
+    Some(VotingStatus {
+        voted,
+        not_voted,
+        deadline: voting.deadline,
+        candidates: voting.candidates.clone(),
+        audience_votes: engine
+            .game()
+            .audience_vote
+            .enabled
+            .then_some(voting.audience.len()),
+        anonymous: voting.anonymous,
+        vote_change_policy: voting.vote_changes.policy,
+        locale,
+    })
+}
+
+async fn handle_vote_button(
+    channel: GameChannel<'_>,
+    press: &ComponentInteraction,
+    vote_prefix: &str,
+    voting_id: u32,
+) -> CmdRet {
+    let Some(target) = press
+        .data
+        .custom_id
+        .strip_prefix(vote_prefix)
+        .and_then(|id| id.parse().ok())
+        .map(UserId::new)
+    else {
+        return Ok(());
+    };
+
+    let session = channel.session().await;
+    let locale = session.locale().await;
+    let result = {
+        let mut lock = session.engine.lock().await;
+        match lock.as_mut() {
+            Some(engine) if engine.voting().is_some_and(|voting| voting.id == voting_id) => {
+                let voter = press.user.id;
+                // everyone else votes as part of the audience, if the game allows that
+                let command = if engine.game().contains_player(voter) {
+                    Command::CastVote { voter, target }
+                } else {
+                    Command::CastAudienceVote { voter, target }
+                };
+
+                engine.handle(command).map_err(Error::from)
+            },
+            _ => Err(t!(locale, "voting.already_ended").into()),
+        }
+    };
+
+    let embed = match result {
+        Ok(events) => {
+            channel.data.persist_or_log().await;
+
+            let description = match events.first() {
+                Some(Event::AudienceVoteCast { .. }) => {
+                    t!(locale, "audience.voted", player = target.mention())
+                },
+                Some(Event::VoteChanged { .. }) => {
+                    t!(locale, "voting.you_changed", player = target.mention())
+                },
+                Some(_) => t!(locale, "voting.you_voted", player = target.mention()),
+                None => t!(
+                    locale,
+                    "voting.you_already_voted",
+                    player = target.mention()
+                ),
+            };
+
+            CreateEmbed::default()
+                .description(description)
+                .color(embed_color())
         },
-        VoteOutcome::NoClearWinner {
-            members_with_equal_votes_count,
-            max_vote_count,
-        } => who_voted_who_description.push_str(&format!(
-            "**{} Leute haben mit {} gleich viele Votes - Gleichstand!**",
-            members_with_equal_votes_count, max_vote_count
-        )),
+        Err(err) => CreateEmbed::default()
+            .title(t!(locale, "errors.title"))
+            .description(localize_error(err.as_ref(), locale))
+            .color(Color::DARK_RED),
+    };
+
+    press
+        .create_response(
+            channel.serenity,
+            CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new()
+                    .embed(embed)
+                    .ephemeral(true),
+            ),
+        )
+        .await?;
+
+    Ok(())
+}
+
+async fn send_voting_reminder(
+    channel: GameChannel<'_>,
+    deadline: DateTime<Utc>,
+    not_voted: &[UserId],
+) -> CmdRet {
+    let locale = channel.session().await.locale().await;
+    let mentions = join_mentions(not_voted);
+
+    channel
+        .channel_id
+        .send_message(
+            channel.serenity,
+            CreateMessage::new().content(mentions).embed(
+                CreateEmbed::default()
+                    .title(t!(locale, "voting.reminder_title"))
+                    .description(t!(
+                        locale,
+                        "voting.reminder",
+                        timestamp = deadline.timestamp()
+                    ))
+                    .color(embed_color()),
+            ),
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Resolves the voting just like `/end-voting` does
+async fn close_voting_on_timeout(
+    channel: GameChannel<'_>,
+    voting_id: u32,
+) -> Result<Option<Vec<Event>>, Error> {
+    let session = channel.session().await;
+    let locale = session.locale().await;
+    let events = {
+        let mut lock = session.engine.lock().await;
+        match lock.as_mut() {
+            Some(engine) if engine.voting().is_some_and(|voting| voting.id == voting_id) => {
+                let events = engine.handle(Command::EndVoting)?;
+                session.record_finished_game(engine, &events).await;
+                events
+            },
+            // someone was faster
+            _ => return Ok(None),
+        }
+    };
+
+    channel.data.persist_or_log().await;
+
+    // the interaction token might have expired by now, so this can't be a reply
+    channel
+        .channel_id
+        .send_message(
+            channel.serenity,
+            CreateMessage::new()
+                .content(t!(locale, "voting.time_up"))
+                .embeds(get_resolution_embeds(&events, locale)),
+        )
+        .await?;
+
+    Ok(Some(events))
+}
+
+#[command(slash_command, rename = "extend-voting", guild_only, check = needs_active_game, check = needs_active_voting, check = is_game_moderator)]
+pub async fn extend_voting(
+    ctx: Context<'_>,
+    #[rename = "dauer"]
+    #[min = 10]
+    #[max = 3600]
+    duration: u32,
+) -> CmdRet {
+    let locale = guild_locale(ctx).await;
+    let deadline = {
+        let session = guild_session(ctx).await;
+        let mut lock = session.engine.lock().await;
+        let engine = lock.as_mut().unwrap();
+
+        let deadline = engine
+            .voting()
+            .and_then(|voting| voting.deadline)
+            .ok_or_else(|| t!(locale, "voting.no_timer"))?
+            + TimeDelta::seconds(duration.into());
+
+        engine.handle(Command::SetVotingDeadline {
+            deadline: Some(deadline),
+        })?;
+        deadline
+    };
+
+    let embed = CreateEmbed::default()
+        .title(t!(locale, "voting.extended_title"))
+        .description(t!(
+            locale,
+            "voting.extended",
+            duration = duration,
+            timestamp = deadline.timestamp()
+        ))
+        .color(embed_color());
+
+    ctx.send(CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
+
+#[command(slash_command, rename = "stop-voting-timer", guild_only, check = needs_active_game, check = needs_active_voting, check = is_game_moderator)]
+pub async fn stop_voting_timer(ctx: Context<'_>) -> CmdRet {
+    let locale = guild_locale(ctx).await;
+    {
+        let session = guild_session(ctx).await;
+        let mut lock = session.engine.lock().await;
+        let engine = lock.as_mut().unwrap();
+
+        if engine
+            .voting()
+            .is_some_and(|voting| voting.deadline.is_none())
+        {
+            return Err(t!(locale, "voting.no_timer").into());
+        }
+
+        engine.handle(Command::SetVotingDeadline { deadline: None })?;
     }
 
-    let reply = create_end_voting_response(who_voted_who_description, &votes, member_died_embed);
+    let embed = CreateEmbed::default()
+        .title(t!(locale, "voting.timer_stopped_title"))
+        .description(t!(locale, "voting.timer_stopped"))
+        .color(embed_color());
+
+    ctx.send(CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
+
+#[command(slash_command, guild_only, check = needs_active_game, check = needs_active_voting, check = is_in_game, check = did_not_vote, check = author_can_vote)]
+pub async fn vote(ctx: Context<'_>, #[rename = "user"] member: Member) -> CmdRet {
+    let locale = guild_locale(ctx).await;
+    let (changed, anonymous) = {
+        let session = guild_session(ctx).await;
+        let mut lock = session.engine.lock().await;
+        let engine = lock.as_mut().unwrap();
+
+        let events = engine.handle(Command::CastVote {
+            voter: ctx.author().id,
+            target: member.user.id,
+        })?;
+
+        (
+            matches!(events.first(), Some(Event::VoteChanged { .. })),
+            engine.voting().is_some_and(|voting| voting.anonymous),
+        )
+    };
+
+    // in an anonymous voting, nobody else should know who voted already
+    let reply = if anonymous {
+        CreateReply::default()
+            .embed(
+                CreateEmbed::default()
+                    .title(t!(locale, "voting.title"))
+                    .description(t!(locale, "voting.you_voted", player = member.mention()))
+                    .color(embed_color()),
+            )
+            .ephemeral(true)
+    } else {
+        let description = if changed {
+            t!(locale, "voting.changed", player = ctx.author().mention())
+        } else {
+            t!(locale, "voting.voted", player = ctx.author().mention())
+        };
+
+        CreateReply::default().embed(
+            CreateEmbed::default()
+                .title(t!(locale, "voting.title"))
+                .description(description)
+                .color(embed_color()),
+        )
+    };
+
     ctx.send(reply).await?;
+    Ok(())
+}
+
+#[command(slash_command, guild_only, ephemeral, check = needs_active_game, check = needs_active_voting, check = is_in_game)]
+pub async fn unvote(ctx: Context<'_>) -> CmdRet {
+    let events = {
+        let session = guild_session(ctx).await;
+        let mut lock = session.engine.lock().await;
+        let engine = lock.as_mut().unwrap();
+
+        engine.handle(Command::WithdrawVote {
+            voter: ctx.author().id,
+        })?
+    };
+
+    let Some(Event::VoteChanged { from, .. }) = events.first() else {
+        unreachable!("Expected the vote to be withdrawn");
+    };
 
-    *voting = None;
+    let locale = guild_locale(ctx).await;
+    let embed = CreateEmbed::default()
+        .title(t!(locale, "voting.title"))
+        .description(t!(locale, "voting.withdrawn", player = from.mention()))
+        .color(embed_color());
+
+    ctx.send(CreateReply::default().embed(embed)).await?;
     Ok(())
 }
 
-/// Sums up all votes of a specific member by providing a member->member map
-async fn sum_up_votes(
-    member_to_member_votes: &HashMap<UserId, UserId>,
-) -> Result<(MemberVoteCount, String), Error> {
-    let mut votes = HashMap::new();
+#[command(slash_command, rename = "set-vote-changes", guild_only, check = needs_active_game, check = is_game_moderator)]
+pub async fn set_vote_changes(
+    ctx: Context<'_>,
+    #[rename = "regel"] policy: VoteChangePolicy,
+    #[rename = "anzeigen"] show: Option<bool>,
+) -> CmdRet {
+    let vote_changes = VoteChanges {
+        policy,
+        show: show.unwrap_or(false),
+    };
 
-    let mut who_voted_who_description = String::new();
-    // Create vote `Member -> Amount of Votes` mapping
-    for (voter, voted) in member_to_member_votes {
-        votes.entry(*voted).and_modify(|num| *num += 1).or_insert(1);
+    {
+        let session = guild_session(ctx).await;
+        let mut lock = session.engine.lock().await;
+        let engine = lock.as_mut().unwrap();
 
-        who_voted_who_description.push_str(&format!(
-            "{} hat {} gevotet!\n\n",
-            voter.mention(),
-            voted.mention()
+        engine.handle(Command::SetVoteChanges { vote_changes })?;
+    }
+
+    let locale = guild_locale(ctx).await;
+    let embed = CreateEmbed::default()
+        .title(t!(locale, "vote_changes.title"))
+        .description(t!(
+            locale,
+            "vote_changes.changed",
+            rule = policy.localize(locale)
         ))
+        .color(embed_color());
+
+    ctx.send(CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
+
+#[command(slash_command, rename = "end-voting", guild_only, check = needs_active_game, check = needs_active_voting, check = is_game_moderator)]
+pub async fn end_voting(ctx: Context<'_>) -> CmdRet {
+    let events = {
+        let session = guild_session(ctx).await;
+        let mut lock = session.engine.lock().await;
+        let engine = lock.as_mut().expect("Expected an active game");
+
+        let events = engine.handle(Command::EndVoting)?;
+        session.record_finished_game(engine, &events).await;
+        events
+    };
+
+    let reply = CreateReply {
+        embeds: get_resolution_embeds(&events, guild_locale(ctx).await),
+        ..Default::default()
+    };
+    ctx.send(reply).await?;
+
+    // this command keeps running during a runoff, so `post_command` would be too late
+    ctx.data().persist_or_log().await;
+
+    if let Some((message, voting_id)) = follow_up_resolution(ctx.into(), &events).await? {
+        drive_voting(ctx.into(), message, voting_id).await?;
     }
 
-    Ok((votes, who_voted_who_description))
+    Ok(())
 }
 
-fn decide_winner(votes: &MemberVoteCount) -> VoteOutcome {
-    let max_num_of_votes = *votes.values().max_by_key(|n| **n).unwrap();
-    let n = votes
-        .values()
-        .filter(|num| **num == max_num_of_votes)
-        .count();
+// Lets the moderator see who voted whom, even in an anonymous voting
+#[command(slash_command, rename = "show-votes", guild_only, ephemeral, check = needs_active_game, check = is_game_moderator)]
+pub async fn show_votes(
+    ctx: Context<'_>,
+    #[rename = "runde"]
+    #[min = 1]
+    round: Option<u32>,
+) -> CmdRet {
+    let embeds = {
+        let session = guild_session(ctx).await;
+        let locale = session.locale().await;
+        let lock = session.engine.lock().await;
+        let engine = lock.as_ref().expect("Expected an active game");
+        let game = engine.game();
 
-    if n > 1 {
-        VoteOutcome::NoClearWinner {
-            members_with_equal_votes_count: n as i32,
-            max_vote_count: max_num_of_votes,
+        let votes = match (round, engine.voting()) {
+            (None, Some(voting)) => vec![(t!(locale, "voting.running"), &voting.map)],
+            (None, None) => game
+                .rounds
+                .last()
+                .map(|record| {
+                    (
+                        t!(locale, "question.round", round = record.round),
+                        &record.votes,
+                    )
+                })
+                .into_iter()
+                .collect(),
+            (Some(round), _) => game
+                .rounds
+                .iter()
+                .filter(|record| record.round == round)
+                .map(|record| {
+                    (
+                        t!(locale, "question.round", round = record.round),
+                        &record.votes,
+                    )
+                })
+                .collect(),
+        };
+        if votes.is_empty() {
+            return Err(t!(locale, "voting.nothing_to_show").into());
         }
+
+        votes
+            .into_iter()
+            .map(|(title, votes)| {
+                let description = if votes.is_empty() {
+                    t!(locale, "voting.no_votes")
+                } else {
+                    describe_votes(votes, false, locale)
+                };
+
+                CreateEmbed::default()
+                    .title(title)
+                    .description(description)
+                    .color(embed_color())
+            })
+            .collect()
+    };
+
+    ctx.send(CreateReply {
+        embeds,
+        ..Default::default()
+    })
+    .await?;
+
+    Ok(())
+}
+
+#[command(slash_command, guild_only, check = needs_active_game)]
+pub async fn history(
+    ctx: Context<'_>,
+    #[rename = "runde"]
+    #[min = 1]
+    round: Option<u32>,
+) -> CmdRet {
+    let embeds = {
+        let session = guild_session(ctx).await;
+        let locale = session.locale().await;
+        let lock = session.engine.lock().await;
+        let game = lock.as_ref().expect("Expected an active game").game();
+
+        match round {
+            Some(round) => {
+                let records = game
+                    .rounds
+                    .iter()
+                    .filter(|record| record.round == round)
+                    .collect::<Vec<_>>();
+                if records.is_empty() {
+                    return Err(t!(locale, "history.not_resolved", round = round).into());
+                }
+
+                records
+                    .into_iter()
+                    .flat_map(|record| get_round_record_embeds(record, locale))
+                    .collect()
+            },
+            None => vec![get_history_overview_embed(&game.rounds, locale)],
+        }
+    };
+
+    ctx.send(CreateReply {
+        embeds,
+        ..Default::default()
+    })
+    .await?;
+
+    Ok(())
+}
+
+fn get_history_overview_embed(records: &[RoundRecord], locale: Locale) -> CreateEmbed {
+    let description = if records.is_empty() {
+        t!(locale, "history.empty")
     } else {
-        let (user, num_votes) = votes
+        records
             .iter()
-            .max_by_key(|(_, amount_votes)| *amount_votes)
-            .map(|(user_id, num)| (user_id.to_owned(), num.to_owned()))
-            .unwrap();
+            .map(|record| {
+                let lost_life = match record.lost_life.as_slice() {
+                    [] => t!(locale, "history.nobody_lost_life"),
+                    players => t!(
+                        locale,
+                        "history.lost_life",
+                        players = join_mentions(players)
+                    ),
+                };
+                let key = if record.runoff {
+                    "history.runoff_entry"
+                } else {
+                    "history.entry"
+                };
 
-        VoteOutcome::ClearWinner { user, num_votes }
+                t!(locale, key, round = record.round, lost_life = lost_life)
+            })
+            .join("\n")
+    };
+
+    CreateEmbed::default()
+        .title(t!(locale, "history.title"))
+        .description(description)
+        .footer(CreateEmbedFooter::new(t!(locale, "history.footer")))
+        .color(embed_color())
+}
+
+fn get_round_record_embeds(record: &RoundRecord, locale: Locale) -> Vec<CreateEmbed> {
+    let mut description = format!("<t:{}:f>\n\n", record.resolved_at.timestamp());
+    description.push_str(&describe_votes(&record.votes, record.anonymous, locale));
+    description.push_str(&describe_outcome(&record.outcome, locale));
+    if !record.lost_life.is_empty() {
+        description.push_str("\n\n");
+        description.push_str(&t!(
+            locale,
+            "history.lost_lives",
+            players = join_mentions(&record.lost_life)
+        ));
+    }
+
+    let title = if record.runoff {
+        t!(locale, "history.runoff_title", round = record.round)
+    } else {
+        t!(locale, "question.round", round = record.round)
+    };
+
+    vec![
+        CreateEmbed::default()
+            .title(title)
+            .description(description)
+            .color(embed_color()),
+        get_vote_counts_embed(
+            &record.counts,
+            &record.audience_counts,
+            &record.twitch_counts,
+            locale,
+        ),
+    ]
+}
+
+/// Builds the result of a voting (or tie-break) from the events of [Command::EndVoting] and
+/// [Command::DecideTie]
+pub fn get_resolution_embeds(events: &[Event], locale: Locale) -> Vec<CreateEmbed> {
+    let mut summary = None;
+    let mut additional_embeds = Vec::new();
+    // a tie can eliminate lots of players at once, they share one embed
+    let mut eliminated = Vec::new();
+    let mut eliminated_at = None;
+
+    for event in events {
+        match event {
+            Event::VotingResolved {
+                votes: member_to_member_votes,
+                anonymous,
+                counts,
+                audience_counts,
+                twitch_counts,
+                changes,
+                outcome,
+            } => {
+                let mut who_voted_who_description =
+                    describe_votes(member_to_member_votes, *anonymous, locale);
+                who_voted_who_description.push_str(&describe_outcome(outcome, locale));
+                summary = Some((
+                    who_voted_who_description,
+                    counts,
+                    audience_counts,
+                    twitch_counts,
+                ));
+
+                // would give away who voted whom
+                if !changes.is_empty() && !anonymous {
+                    additional_embeds.push(get_vote_changes_embed(changes, locale));
+                }
+            },
+            Event::TieBroken { tie_break, losers } => {
+                additional_embeds.push(get_tie_broken_embed(*tie_break, losers, locale))
+            },
+            Event::TieBreakPending { candidates } => additional_embeds.push(
+                CreateEmbed::default()
+                    .title(t!(locale, "tie_break.tie"))
+                    .description(t!(
+                        locale,
+                        "tie_break.pending",
+                        candidates = join_mentions(candidates)
+                    ))
+                    .color(embed_color()),
+            ),
+            Event::RunoffStarted { candidates } => additional_embeds.push(
+                CreateEmbed::default()
+                    .title(t!(locale, "tie_break.tie"))
+                    .description(t!(
+                        locale,
+                        "tie_break.runoff",
+                        candidates = join_mentions(candidates)
+                    ))
+                    .color(embed_color()),
+            ),
+            Event::PlayerEliminated { player } => {
+                eliminated_at.get_or_insert(additional_embeds.len());
+                eliminated.push(*player);
+            },
+            Event::FinaleStarted {
+                players,
+                questions_per_player,
+            } => additional_embeds.push(get_finale_started_embed(
+                *players,
+                *questions_per_player,
+                locale,
+            )),
+            Event::GameFinished { winner, standings } => {
+                additional_embeds.push(get_game_finished_embed(*winner, standings, locale))
+            },
+            _ => (),
+        }
+    }
+
+    if let Some(index) = eliminated_at {
+        let description = match &eliminated[..] {
+            [player] => t!(locale, "voting.eliminated", player = player.mention()),
+            players => t!(
+                locale,
+                "voting.eliminated_many",
+                players = join_mentions(players)
+            ),
+        };
+        additional_embeds.insert(
+            index,
+            CreateEmbed::default()
+                .description(description)
+                .color(embed_color()),
+        );
+    }
+
+    match summary {
+        Some((who_voted_who_description, votes, audience_votes, twitch_votes)) => {
+            create_end_voting_embeds(
+                who_voted_who_description,
+                votes,
+                audience_votes,
+                twitch_votes,
+                additional_embeds,
+                locale,
+            )
+        },
+        None => additional_embeds,
+    }
+}
+
+fn join_mentions(players: &[UserId]) -> String {
+    players.iter().map(|player| player.mention()).join(", ")
+}
+
+fn get_tie_broken_embed(tie_break: TieBreak, losers: &[UserId], locale: Locale) -> CreateEmbed {
+    let description = match losers {
+        [] => t!(locale, "tie_break.nobody_loses"),
+        [loser] => t!(locale, "tie_break.loses", player = loser.mention()),
+        losers => t!(locale, "tie_break.lose", players = join_mentions(losers)),
+    };
+
+    CreateEmbed::default()
+        .title(t!(
+            locale,
+            "tie_break.broken_title",
+            rule = tie_break.localize(locale)
+        ))
+        .description(description)
+        .color(embed_color())
+}
+
+fn describe_votes(
+    member_to_member_votes: &HashMap<UserId, UserId>,
+    anonymous: bool,
+    locale: Locale,
+) -> String {
+    if anonymous {
+        return format!("{}\n\n", tr(locale, "voting.was_anonymous"));
+    }
+
+    let mut who_voted_who_description = String::new();
+
+    for (voter, voted) in member_to_member_votes {
+        who_voted_who_description.push_str(&t!(
+            locale,
+            "voting.voted_for",
+            voter = voter.mention(),
+            player = voted.mention()
+        ));
+        who_voted_who_description.push_str("\n\n");
     }
+
+    who_voted_who_description
 }
 
-fn get_voting_count_embed(votes: &HashMap<UserId, i32>) -> CreateEmbed {
+fn get_vote_changes_embed(changes: &[VoteChange], locale: Locale) -> CreateEmbed {
+    let description = changes
+        .iter()
+        .map(|change| match change.to {
+            Some(to) => format!(
+                "🔁 {}: {} ➜ {}",
+                change.voter.mention(),
+                change.from.mention(),
+                to.mention()
+            ),
+            None => t!(
+                locale,
+                "voting.change_withdrawn",
+                voter = change.voter.mention(),
+                player = change.from.mention()
+            ),
+        })
+        .join("\n");
+
+    CreateEmbed::default()
+        .title(t!(locale, "voting.changes_title"))
+        .description(description)
+        .color(embed_color())
+}
+
+fn describe_outcome(outcome: &VoteOutcome, locale: Locale) -> String {
+    match outcome {
+        VoteOutcome::ClearWinner { user, num_votes } => t!(
+            locale,
+            "voting.outcome_clear",
+            player = user.mention(),
+            votes = num_votes
+        ),
+        VoteOutcome::NoClearWinner {
+            members_with_equal_votes,
+            max_vote_count,
+        } => t!(
+            locale,
+            "voting.outcome_tie",
+            players = members_with_equal_votes.len(),
+            votes = max_vote_count
+        ),
+        VoteOutcome::NoVotes => format!("**{}**", tr(locale, "voting.no_votes")),
+    }
+}
+
+pub fn get_game_finished_embed(
+    winner: Option<UserId>,
+    standings: &[Standing],
+    locale: Locale,
+) -> CreateEmbed {
+    let description = match winner {
+        Some(winner) => t!(locale, "game.won", player = winner.mention()),
+        None => t!(locale, "game.nobody_left"),
+    };
+
+    let mut embed = CreateEmbed::default()
+        .title(t!(locale, "game.finished_title"))
+        .description(description)
+        .color(embed_color());
+    if !standings.is_empty() {
+        embed = embed.field(
+            t!(locale, "standings.final"),
+            format_standings(standings, locale),
+            false,
+        );
+    }
+
+    embed
+}
+
+/// The audience is counted separately, but in the same embed, as Discord allows at most 10
+/// embeds per message
+fn get_vote_counts_embed(
+    votes: &HashMap<UserId, i32>,
+    audience_votes: &HashMap<UserId, i32>,
+    twitch_votes: &HashMap<UserId, i32>,
+    locale: Locale,
+) -> CreateEmbed {
+    let mut description = describe_vote_counts(votes);
+    for (title, extra_votes) in [
+        ("voting.audience_vote_count", audience_votes),
+        ("voting.twitch_vote_count", twitch_votes),
+    ] {
+        if !extra_votes.is_empty() {
+            description.push_str(&format!("\n**{}**\n", tr(locale, title)));
+            description.push_str(&describe_vote_counts(extra_votes));
+        }
+    }
+
+    CreateEmbed::default()
+        .title(tr(locale, "voting.vote_count"))
+        .description(description)
+        .color(embed_color())
+}
+
+fn describe_vote_counts(votes: &HashMap<UserId, i32>) -> String {
     let mut description = String::new();
 
     for (user, amount_votes) in votes.iter().sorted_by(|a, b| a.1.cmp(b.1)) {
         description.push_str(&format!("`[{:>2}]` - {}\n", amount_votes, user.mention()));
     }
 
-    CreateEmbed::default()
-        .title("Anzahl der Votes")
-        .description(description)
-        .color(DEFAULT_COLOR)
+    description
 }
 
-fn create_end_voting_response(
+fn create_end_voting_embeds(
     who_voted_who_description: String,
     votes: &HashMap<UserId, i32>,
-    member_died_embed: Option<CreateEmbed>,
-) -> CreateReply {
-    let mut reply = CreateReply::default();
+    audience_votes: &HashMap<UserId, i32>,
+    twitch_votes: &HashMap<UserId, i32>,
+    additional_embeds: Vec<CreateEmbed>,
+    locale: Locale,
+) -> Vec<CreateEmbed> {
+    let mut embeds = Vec::new();
     // overview - who voted which person?
-    reply = reply.embed(
+    embeds.push(
         CreateEmbed::default()
-            .title("Voting ist zuende.")
+            .title(t!(locale, "voting.ended"))
             .description(who_voted_who_description)
-            .color(DEFAULT_COLOR),
+            .color(embed_color()),
     );
 
     // overview of all votes
-    reply = reply.embed(get_voting_count_embed(votes));
+    embeds.push(get_vote_counts_embed(
+        votes,
+        audience_votes,
+        twitch_votes,
+        locale,
+    ));
 
-    // additional info whether a member died in this round
-    if let Some(member_died_embed) = member_died_embed {
-        reply = reply.embed(member_died_embed);
-    }
-    reply
+    // additional info whether a member died in this round or the game is over
+    embeds.extend(additional_embeds);
+    embeds
 }
@@ -0,0 +1,317 @@
+use std::{
+    env,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
+
+use poise::serenity_prelude::UserId;
+use serde::{Deserialize, Serialize};
+
+use crate::DEFAULT_COLOR;
+
+pub const DEFAULT_CONFIG_PATH: &str = "config.toml";
+
+// the ranges `/config` accepts as well
+pub const MIN_LIVES: i64 = 1;
+pub const MAX_LIVES: i64 = 99;
+pub const MIN_TIMEOUT: i64 = 10;
+pub const MAX_TIMEOUT: i64 = 24 * 60 * 60;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    #[error("Die Konfiguration `{}` konnte nicht gelesen werden: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("Die Konfiguration `{}` ist ungültig: {source}", path.display())]
+    Toml {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+
+    #[error("Die Einstellung `{0}` hat einen ungültigen Wert: `{1}`")]
+    InvalidSetting(&'static str, String),
+
+    #[error("Die Einstellung `{setting}` muss zwischen {min} und {max} liegen, ist aber {value}.")]
+    OutOfRange {
+        setting: &'static str,
+        value: i64,
+        min: i64,
+        max: i64,
+    },
+}
+
+/// Everything that can be set in the config file, every value is optional
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub bot: BotConfig,
+    pub game: GameConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BotConfig {
+    pub owners: Vec<UserId>,
+    pub log_level: LogLevel,
+    // the color of every embed
+    pub color: EmbedColor,
+}
+
+impl Default for BotConfig {
+    fn default() -> Self {
+        Self {
+            owners: vec![UserId::new(350749990681051149)],
+            log_level: LogLevel(tracing::Level::INFO),
+            color: EmbedColor(DEFAULT_COLOR),
+        }
+    }
+}
+
+/// The defaults of a game, each guild can override them with `/config`. The timeouts are in
+/// seconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GameConfig {
+    // the lives of a player if nobody said otherwise
+    pub lives: i32,
+    // how long the yes/no buttons of a confirmation wait
+    pub confirm_timeout: u32,
+    // how long players can join via the buttons of the lobby
+    pub lobby_timeout: u32,
+    // how long the moderator has to decide a tie via buttons
+    pub tie_decision_timeout: u32,
+    // how long the moderator can judge an answer via the buttons of the question
+    pub answer_timeout: u32,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            lives: 3,
+            confirm_timeout: 60,
+            lobby_timeout: 60 * 60,
+            tie_decision_timeout: 5 * 60,
+            answer_timeout: 10 * 60,
+        }
+    }
+}
+
+impl GameConfig {
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        check_range("game.lives", self.lives.into(), MIN_LIVES, MAX_LIVES)?;
+
+        for (setting, timeout) in [
+            ("game.confirm_timeout", self.confirm_timeout),
+            ("game.lobby_timeout", self.lobby_timeout),
+            ("game.tie_decision_timeout", self.tie_decision_timeout),
+            ("game.answer_timeout", self.answer_timeout),
+        ] {
+            check_range(setting, timeout.into(), MIN_TIMEOUT, MAX_TIMEOUT)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn confirm_timeout(&self) -> Duration {
+        Duration::from_secs(self.confirm_timeout.into())
+    }
+
+    pub fn lobby_timeout(&self) -> Duration {
+        Duration::from_secs(self.lobby_timeout.into())
+    }
+
+    pub fn tie_decision_timeout(&self) -> Duration {
+        Duration::from_secs(self.tie_decision_timeout.into())
+    }
+
+    pub fn answer_timeout(&self) -> Duration {
+        Duration::from_secs(self.answer_timeout.into())
+    }
+}
+
+fn check_range(setting: &'static str, value: i64, min: i64, max: i64) -> Result<(), ConfigError> {
+    if (min..=max).contains(&value) {
+        Ok(())
+    } else {
+        Err(ConfigError::OutOfRange {
+            setting,
+            value,
+            min,
+            max,
+        })
+    }
+}
+
+/// What a guild changed of the [GameConfig] with `/config`, everything else follows the config
+/// file
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameConfigOverrides {
+    pub lives: Option<i32>,
+    pub confirm_timeout: Option<u32>,
+    pub lobby_timeout: Option<u32>,
+    pub tie_decision_timeout: Option<u32>,
+    pub answer_timeout: Option<u32>,
+}
+
+impl GameConfigOverrides {
+    pub fn apply(&self, config: GameConfig) -> GameConfig {
+        GameConfig {
+            lives: self.lives.unwrap_or(config.lives),
+            confirm_timeout: self.confirm_timeout.unwrap_or(config.confirm_timeout),
+            lobby_timeout: self.lobby_timeout.unwrap_or(config.lobby_timeout),
+            tie_decision_timeout: self
+                .tie_decision_timeout
+                .unwrap_or(config.tie_decision_timeout),
+            answer_timeout: self.answer_timeout.unwrap_or(config.answer_timeout),
+        }
+    }
+}
+
+/// A level of `tracing`, e.g. `info` or `debug`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct LogLevel(pub tracing::Level);
+
+impl FromStr for LogLevel {
+    type Err = ConfigError;
+
+    fn from_str(level: &str) -> Result<Self, Self::Err> {
+        level
+            .parse()
+            .map(LogLevel)
+            .map_err(|_| ConfigError::InvalidSetting("bot.log_level", level.to_owned()))
+    }
+}
+
+impl TryFrom<String> for LogLevel {
+    type Error = ConfigError;
+
+    fn try_from(level: String) -> Result<Self, Self::Error> {
+        level.parse()
+    }
+}
+
+/// A color written as hex, e.g. `#87CEEB`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct EmbedColor(pub u32);
+
+impl FromStr for EmbedColor {
+    type Err = ConfigError;
+
+    fn from_str(color: &str) -> Result<Self, Self::Err> {
+        let hex = color.trim_start_matches('#');
+        match u32::from_str_radix(hex, 16) {
+            Ok(value) if hex.len() == 6 => Ok(EmbedColor(value)),
+            _ => Err(ConfigError::InvalidSetting("bot.color", color.to_owned())),
+        }
+    }
+}
+
+impl TryFrom<String> for EmbedColor {
+    type Error = ConfigError;
+
+    fn try_from(color: String) -> Result<Self, Self::Error> {
+        color.parse()
+    }
+}
+
+impl Config {
+    /// Reads the config file, applies the environment variables on top and validates the result.
+    ///
+    /// A missing file results in the defaults, as every value is optional.
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let mut config = match std::fs::read_to_string(path) {
+            Ok(content) => Self::from_toml(&content).map_err(|source| ConfigError::Toml {
+                path: path.to_owned(),
+                source,
+            })?,
+            Err(err) if err.kind() == ErrorKind::NotFound => Self::default(),
+            Err(source) => {
+                return Err(ConfigError::Io {
+                    path: path.to_owned(),
+                    source,
+                })
+            },
+        };
+
+        config.apply_overrides(|name| env::var(name).ok())?;
+        config.game.validate()?;
+        Ok(config)
+    }
+
+    pub fn from_toml(content: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(content)
+    }
+
+    /// Overrides the values of the file with the variables `lookup` returns, see the README for
+    /// their names
+    pub fn apply_overrides(
+        &mut self,
+        lookup: impl Fn(&'static str) -> Option<String>,
+    ) -> Result<(), ConfigError> {
+        let setting = |name: &'static str| lookup(name).filter(|value| !value.trim().is_empty());
+
+        if let Some(owners) = setting("OWNERS") {
+            self.bot.owners = owners
+                .split(',')
+                .map(|owner| {
+                    owner
+                        .trim()
+                        .parse()
+                        .map_err(|_| ConfigError::InvalidSetting("OWNERS", owners.clone()))
+                })
+                .collect::<Result<_, _>>()?;
+        }
+        if let Some(level) = setting("LOG_LEVEL") {
+            self.bot.log_level = level.parse()?;
+        }
+        if let Some(color) = setting("EMBED_COLOR") {
+            self.bot.color = color.parse()?;
+        }
+
+        let game = &mut self.game;
+        override_number(&mut game.lives, "DEFAULT_LIVES", setting("DEFAULT_LIVES"))?;
+        override_number(
+            &mut game.confirm_timeout,
+            "CONFIRM_TIMEOUT",
+            setting("CONFIRM_TIMEOUT"),
+        )?;
+        override_number(
+            &mut game.lobby_timeout,
+            "LOBBY_TIMEOUT",
+            setting("LOBBY_TIMEOUT"),
+        )?;
+        override_number(
+            &mut game.tie_decision_timeout,
+            "TIE_DECISION_TIMEOUT",
+            setting("TIE_DECISION_TIMEOUT"),
+        )?;
+        override_number(
+            &mut game.answer_timeout,
+            "ANSWER_TIMEOUT",
+            setting("ANSWER_TIMEOUT"),
+        )?;
+
+        Ok(())
+    }
+}
+
+fn override_number<T: FromStr>(
+    value: &mut T,
+    name: &'static str,
+    raw: Option<String>,
+) -> Result<(), ConfigError> {
+    if let Some(raw) = raw {
+        *value = raw
+            .trim()
+            .parse()
+            .map_err(|_| ConfigError::InvalidSetting(name, raw))?;
+    }
+    Ok(())
+}
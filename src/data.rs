@@ -1,23 +1,159 @@
+use std::{collections::HashMap, sync::Arc};
+
+use poise::serenity_prelude::GuildId;
 use tokio::sync::Mutex;
 
-use crate::game::{Game, Voting};
+use crate::{
+    config::{Config, GameConfig, GameConfigOverrides},
+    engine::{Event, GameEngine},
+    i18n::Locale,
+    overlay::{Overlay, OverlayState, OverlayStates},
+    persistence::{PersistenceError, SessionSnapshot, Snapshot, SnapshotStore},
+    questions::QuestionBank,
+    stats::StatsStore,
+};
+
+/// The game (and its voting) of a single guild, the stats of its previous games, the language
+/// it chose and what it changed of the game defaults
+#[derive(Default)]
+pub struct Session {
+    pub engine: Mutex<Option<GameEngine>>,
+    pub stats: Mutex<StatsStore>,
+    pub locale: Mutex<Locale>,
+    pub config: Mutex<GameConfigOverrides>,
+}
+
+impl Session {
+    fn from_snapshot(snapshot: SessionSnapshot) -> Self {
+        Session {
+            engine: Mutex::new(snapshot.engine),
+            stats: Mutex::new(snapshot.stats),
+            locale: Mutex::new(snapshot.locale),
+            config: Mutex::new(snapshot.config),
+        }
+    }
+
+    async fn snapshot(&self) -> SessionSnapshot {
+        SessionSnapshot {
+            engine: self.engine.lock().await.clone(),
+            stats: self.stats.lock().await.clone(),
+            locale: *self.locale.lock().await,
+            config: *self.config.lock().await,
+        }
+    }
+
+    pub async fn locale(&self) -> Locale {
+        *self.locale.lock().await
+    }
+
+    /// Adds the game to the stats if the events of a command finished it, see
+    /// [StatsStore::record_game]. Every command that can end the game passes its events here.
+    pub async fn record_finished_game(&self, engine: &GameEngine, events: &[Event]) {
+        if events
+            .iter()
+            .any(|event| matches!(event, Event::GameFinished { .. }))
+        {
+            self.stats.lock().await.record_game(engine.game());
+        }
+    }
+}
 
 pub struct Data {
-    pub game: Mutex<Option<Game>>,
-    pub voting: Mutex<Option<Voting>>,
+    sessions: Mutex<HashMap<GuildId, Arc<Session>>>,
+    pub store: SnapshotStore,
+    pub questions: QuestionBank,
+    pub config: Config,
+    pub overlay: Overlay,
 }
 
 impl Data {
-    pub fn new() -> Self {
+    pub fn new(store: SnapshotStore, questions: QuestionBank, config: Config) -> Self {
+        Self::from_snapshot(store, questions, config, Snapshot::default())
+    }
+
+    pub fn from_snapshot(
+        store: SnapshotStore,
+        questions: QuestionBank,
+        config: Config,
+        snapshot: Snapshot,
+    ) -> Self {
+        let sessions = snapshot
+            .sessions
+            .into_iter()
+            .map(|(guild_id, session)| (guild_id, Arc::new(Session::from_snapshot(session))))
+            .collect();
+
         Data {
-            game: Mutex::new(None),
-            voting: Mutex::new(None),
+            sessions: Mutex::new(sessions),
+            store,
+            questions,
+            config,
+            overlay: Overlay::default(),
         }
     }
-}
 
-impl Default for Data {
-    fn default() -> Self {
-        Self::new()
+    /// The defaults of the config file with the overrides of the guild applied
+    pub async fn game_config(&self, guild_id: GuildId) -> GameConfig {
+        self.session(guild_id)
+            .await
+            .config
+            .lock()
+            .await
+            .apply(self.config.game)
+    }
+
+    /// Returns the session of the guild, creating an empty one if there is none yet
+    pub async fn session(&self, guild_id: GuildId) -> Arc<Session> {
+        self.sessions
+            .lock()
+            .await
+            .entry(guild_id)
+            .or_default()
+            .clone()
+    }
+
+    pub async fn sessions(&self) -> Vec<(GuildId, Arc<Session>)> {
+        self.sessions
+            .lock()
+            .await
+            .iter()
+            .map(|(guild_id, session)| (*guild_id, session.clone()))
+            .collect()
+    }
+
+    /// Writes the current state of every session to the [SnapshotStore]
+    pub async fn persist(&self) -> Result<(), PersistenceError> {
+        self.store
+            .save(async {
+                let mut snapshot = Snapshot::default();
+                for (guild_id, session) in self.sessions().await {
+                    snapshot.sessions.insert(guild_id, session.snapshot().await);
+                }
+                snapshot
+            })
+            .await
+    }
+
+    /// Like [Data::persist], but a failed write is only logged instead of failing the command.
+    /// The overlay is updated here too.
+    pub async fn persist_or_log(&self) {
+        if let Err(error) = self.persist().await {
+            tracing::error!(error = %error, "failed to persist the session");
+        }
+        self.publish_overlay().await;
+    }
+
+    /// Sends the state of every game to the connected overlays
+    pub async fn publish_overlay(&self) {
+        let mut states = OverlayStates::new();
+        for (guild_id, session) in self.sessions().await {
+            let locale = session.locale().await;
+            if let Some(engine) = session.engine.lock().await.as_ref() {
+                states.insert(guild_id, OverlayState::new(engine, locale));
+            }
+        }
+
+        // the names are looked up without holding the locks
+        self.overlay.publish(states).await;
     }
 }
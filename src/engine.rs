@@ -0,0 +1,1341 @@
+use std::{collections::HashMap, fmt};
+
+use chrono::{DateTime, Utc};
+use poise::serenity_prelude::{ChannelId, Mentionable, UserId};
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    game::{
+        first_round,
+        Answer,
+        AskedQuestion,
+        AudienceVote,
+        Elimination,
+        Finale,
+        FinaleRules,
+        Game,
+        GhostVote,
+        PlayerError,
+        RoundRecord,
+        Standing,
+        TieBreak,
+        VoteChange,
+        VoteChangePolicy,
+        VoteChanges,
+        Voting,
+        GHOST_VOTES_PER_PLAYER_VOTE,
+    },
+    i18n::{Locale, Localize},
+    questions::Question,
+    rules::{VoteRuleError, VoteRules},
+    t,
+};
+
+/// Member -> amount of votes they received
+pub type MemberVoteCount = HashMap<UserId, i32>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Phase {
+    /// Players are being added, nothing has been played yet
+    Lobby,
+    QuestionRound,
+    Voting,
+    /// A voting was resolved, the next round can be started
+    Resolution,
+    /// The last two players answer questions until one of them wins, see [Finale]
+    Finale,
+    Finished,
+}
+
+impl Localize for Phase {
+    fn localize(&self, locale: Locale) -> String {
+        t!(
+            locale,
+            match self {
+                Phase::Lobby => "phase.lobby",
+                Phase::QuestionRound => "phase.question_round",
+                Phase::Voting => "phase.voting",
+                Phase::Resolution => "phase.resolution",
+                Phase::Finale => "phase.finale",
+                Phase::Finished => "phase.finished",
+            }
+        )
+    }
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.localize(Locale::default()))
+    }
+}
+
+#[non_exhaustive]
+#[derive(Debug, thiserror::Error)]
+pub enum EngineError {
+    InvalidPhase(Phase),
+    NoActiveVoting,
+    VotingAlreadyActive,
+    NotEnoughPlayers,
+    AlreadyVoted(UserId),
+    NotVoted(UserId),
+    WithdrawNotAllowed,
+    NotACandidate(UserId),
+    TieBreakPending,
+    NoPendingTie,
+    AudienceVoteDisabled,
+    PlayerInAudience(UserId),
+    LobbyClosed,
+    LobbyFull(u32),
+    UnknownQuestion(usize),
+    QuestionClosed(usize),
+    NotOnTurn(UserId),
+    FinaleQuestionOpen,
+    Player(#[from] PlayerError),
+    Rule(#[from] VoteRuleError),
+}
+
+impl Localize for EngineError {
+    fn localize(&self, locale: Locale) -> String {
+        match self {
+            EngineError::InvalidPhase(phase) => {
+                t!(
+                    locale,
+                    "errors.engine.invalid_phase",
+                    phase = phase.localize(locale)
+                )
+            },
+            EngineError::NoActiveVoting => t!(locale, "errors.engine.no_active_voting"),
+            EngineError::VotingAlreadyActive => t!(locale, "errors.engine.voting_already_active"),
+            EngineError::NotEnoughPlayers => t!(locale, "errors.engine.not_enough_players"),
+            EngineError::AlreadyVoted(player) => {
+                t!(
+                    locale,
+                    "errors.engine.already_voted",
+                    player = player.mention()
+                )
+            },
+            EngineError::NotVoted(player) => {
+                t!(locale, "errors.engine.not_voted", player = player.mention())
+            },
+            EngineError::WithdrawNotAllowed => t!(locale, "errors.engine.withdraw_not_allowed"),
+            EngineError::NotACandidate(player) => {
+                t!(
+                    locale,
+                    "errors.engine.not_a_candidate",
+                    player = player.mention()
+                )
+            },
+            EngineError::TieBreakPending => t!(locale, "errors.engine.tie_break_pending"),
+            EngineError::NoPendingTie => t!(locale, "errors.engine.no_pending_tie"),
+            EngineError::AudienceVoteDisabled => t!(locale, "errors.engine.audience_vote_disabled"),
+            EngineError::PlayerInAudience(player) => {
+                t!(
+                    locale,
+                    "errors.engine.player_in_audience",
+                    player = player.mention()
+                )
+            },
+            EngineError::LobbyClosed => t!(locale, "errors.engine.lobby_closed"),
+            EngineError::LobbyFull(max_players) => {
+                t!(
+                    locale,
+                    "errors.engine.lobby_full",
+                    max_players = max_players
+                )
+            },
+            EngineError::UnknownQuestion(_) => t!(locale, "errors.engine.unknown_question"),
+            EngineError::QuestionClosed(_) => t!(locale, "errors.engine.question_closed"),
+            EngineError::NotOnTurn(player) => {
+                t!(
+                    locale,
+                    "errors.engine.not_on_turn",
+                    player = player.mention()
+                )
+            },
+            EngineError::FinaleQuestionOpen => t!(locale, "errors.engine.finale_question_open"),
+            EngineError::Player(err) => err.localize(locale),
+            EngineError::Rule(err) => err.localize(locale),
+        }
+    }
+}
+
+impl fmt::Display for EngineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.localize(Locale::default()))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Command {
+    Join {
+        player: UserId,
+        lives: i32,
+    },
+    Leave {
+        player: UserId,
+    },
+    /// `None` removes the limit
+    SetMaxPlayers {
+        max_players: Option<u32>,
+    },
+    CloseLobby,
+    SetLives {
+        player: UserId,
+        lives: i32,
+    },
+    StartQuestionRound,
+    AskQuestion {
+        player: UserId,
+        question: Question,
+    },
+    /// `question` is the index into [Game::asked_questions]
+    JudgeAnswer {
+        question: usize,
+        correct: bool,
+    },
+    /// Closes a question nobody judged, it counts as neither right nor wrong
+    SkipQuestion {
+        question: usize,
+    },
+    /// A runoff of an `anonymous` voting is anonymous as well
+    StartVoting {
+        creator: UserId,
+        deadline: Option<DateTime<Utc>>,
+        anonymous: bool,
+    },
+    /// `None` removes the timer of the active voting
+    SetVotingDeadline {
+        deadline: Option<DateTime<Utc>>,
+    },
+    CancelVoting,
+    CastVote {
+        voter: UserId,
+        target: UserId,
+    },
+    /// Only allowed by [VoteChangePolicy::ChangeOrWithdraw]
+    WithdrawVote {
+        voter: UserId,
+    },
+    /// A vote of someone that isn't part of the game
+    CastAudienceVote {
+        voter: UserId,
+        target: UserId,
+    },
+    /// A `!vote` from the Twitch chat, counted apart from every other vote. The last vote of a
+    /// viewer counts.
+    CastTwitchVote {
+        viewer: String,
+        target: UserId,
+    },
+    SetAudienceVote {
+        audience_vote: AudienceVote,
+    },
+    SetGhostVote {
+        ghost_vote: GhostVote,
+    },
+    SetVoteRules {
+        vote_rules: VoteRules,
+    },
+    /// Applies to the next voting
+    SetVoteChanges {
+        vote_changes: VoteChanges,
+    },
+    EndVoting,
+    SetTieBreak {
+        tie_break: TieBreak,
+    },
+    /// Resolves a tie with [TieBreak::Moderator]
+    DecideTie {
+        loser: UserId,
+    },
+    /// Applies once the last two players are left
+    SetFinaleRules {
+        finale_rules: FinaleRules,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VoteOutcome {
+    NoVotes,
+    NoClearWinner {
+        members_with_equal_votes: Vec<UserId>,
+        max_vote_count: i32,
+    },
+    ClearWinner {
+        user: UserId,
+        num_votes: i32,
+    },
+}
+
+/// Everything that changed the state of a game.
+///
+/// This is part of the `/export-game` schema, the variant name is stored in `type`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum Event {
+    PlayerJoined {
+        player: UserId,
+        lives: i32,
+    },
+    PlayerLeft {
+        player: UserId,
+    },
+    LivesChanged {
+        player: UserId,
+        lives: i32,
+    },
+    MaxPlayersChanged {
+        max_players: Option<u32>,
+    },
+    LobbyClosed,
+    QuestionRoundStarted,
+    QuestionAsked {
+        round: u32,
+        player: UserId,
+        question: Question,
+    },
+    AnswerJudged {
+        round: u32,
+        player: UserId,
+        answer: Answer,
+    },
+    VotingStarted {
+        creator: UserId,
+        deadline: Option<DateTime<Utc>>,
+        #[serde(default)]
+        anonymous: bool,
+    },
+    VotingDeadlineChanged {
+        deadline: Option<DateTime<Utc>>,
+    },
+    VotingCancelled,
+    VoteCast {
+        voter: UserId,
+        target: UserId,
+    },
+    /// `to` is `None` if the vote was withdrawn
+    VoteChanged {
+        voter: UserId,
+        from: UserId,
+        to: Option<UserId>,
+    },
+    AudienceVoteCast {
+        voter: UserId,
+        target: UserId,
+    },
+    TwitchVoteCast {
+        viewer: String,
+        target: UserId,
+    },
+    AudienceVoteChanged {
+        audience_vote: AudienceVote,
+    },
+    GhostVoteChanged {
+        ghost_vote: GhostVote,
+    },
+    VoteRulesChanged {
+        vote_rules: VoteRules,
+    },
+    VoteChangesChanged {
+        vote_changes: VoteChanges,
+    },
+    /// `counts` only contains the votes of the players, `outcome` includes the audience if
+    /// [AudienceVote::votes_per_player_vote] is set. `votes` shouldn't be published if the voting
+    /// is `anonymous`.
+    VotingResolved {
+        votes: HashMap<UserId, UserId>,
+        #[serde(default)]
+        anonymous: bool,
+        counts: MemberVoteCount,
+        #[serde(default)]
+        audience_counts: MemberVoteCount,
+        /// Only shown, these never change the `outcome`
+        #[serde(default)]
+        twitch_counts: MemberVoteCount,
+        /// Empty unless [VoteChanges::show] is set
+        #[serde(default)]
+        changes: Vec<VoteChange>,
+        outcome: VoteOutcome,
+    },
+    LifeLost {
+        player: UserId,
+        lives_left: i32,
+    },
+    PlayerEliminated {
+        player: UserId,
+    },
+    TieBreakChanged {
+        tie_break: TieBreak,
+    },
+    /// `losers` is empty if the tie couldn't be broken
+    TieBroken {
+        tie_break: TieBreak,
+        losers: Vec<UserId>,
+    },
+    /// The moderator has to send a [Command::DecideTie]
+    TieBreakPending {
+        candidates: Vec<UserId>,
+    },
+    /// A new voting between the tied players was started
+    RunoffStarted {
+        candidates: Vec<UserId>,
+    },
+    FinaleRulesChanged {
+        finale_rules: FinaleRules,
+    },
+    /// The first of the `players` gets the first question
+    FinaleStarted {
+        players: [UserId; 2],
+        questions_per_player: u32,
+    },
+    /// `winner` is `None` if nobody is alive anymore. `standings` go from first to last place,
+    /// see [Game::standings].
+    GameFinished {
+        winner: Option<UserId>,
+        #[serde(default)]
+        standings: Vec<Standing>,
+    },
+}
+
+/// An [Event] of the game log, with the time it happened at
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogEntry {
+    pub at: DateTime<Utc>,
+    pub event: Event,
+}
+
+/// The rules of the game, free of anything Discord-related.
+///
+/// Every change goes through [GameEngine::handle], which validates the [Command] against the
+/// current [Phase] and reports what happened as a list of [Event]s. Those are appended to the
+/// log of the [Game] as well.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameEngine {
+    game: Game,
+    voting: Option<Voting>,
+    phase: Phase,
+    votings_started: u32,
+    // the players the moderator has to choose from
+    pending_tie: Option<Vec<UserId>>,
+}
+
+impl GameEngine {
+    pub fn new(creator: UserId, moderator: UserId, channel_id: ChannelId) -> Self {
+        Self {
+            game: Game {
+                creator,
+                moderator,
+                channel_id,
+                members: HashMap::new(),
+                eliminations: Vec::new(),
+                tie_break: TieBreak::default(),
+                vote_rules: VoteRules::default(),
+                ghost_vote: GhostVote::default(),
+                vote_changes: VoteChanges::default(),
+                audience_vote: AudienceVote::default(),
+                finale_rules: FinaleRules::default(),
+                finale: None,
+                max_players: None,
+                lobby_closed: false,
+                round: first_round(),
+                asked_questions: Vec::new(),
+                rounds: Vec::new(),
+                log: Vec::new(),
+            },
+            voting: None,
+            phase: Phase::Lobby,
+            votings_started: 0,
+            pending_tie: None,
+        }
+    }
+
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    pub fn voting(&self) -> Option<&Voting> {
+        self.voting.as_ref()
+    }
+
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    /// The players of a tie the moderator has to decide, see [TieBreak::Moderator]
+    pub fn pending_tie(&self) -> Option<&[UserId]> {
+        self.pending_tie.as_deref()
+    }
+
+    /// The index of the latest question of the round that wasn't judged yet
+    pub fn open_question(&self) -> Option<usize> {
+        self.game
+            .asked_questions
+            .iter()
+            .rposition(|asked| asked.round == self.game.round && asked.answer.is_none())
+    }
+
+    pub fn living_players(&self) -> impl Iterator<Item = UserId> + '_ {
+        self.game
+            .members
+            .iter()
+            .filter(|(_, lives)| **lives > 0)
+            .map(|(player, _)| *player)
+    }
+
+    pub fn handle(&mut self, command: Command) -> Result<Vec<Event>, EngineError> {
+        if self.phase == Phase::Finished {
+            return Err(EngineError::InvalidPhase(self.phase));
+        }
+
+        let events = match command {
+            Command::Join { player, lives } => self.join(player, lives),
+            Command::Leave { player } => self.leave(player),
+            Command::SetMaxPlayers { max_players } => self.set_max_players(max_players),
+            Command::CloseLobby => self.close_lobby(),
+            Command::SetLives { player, lives } => self.set_lives(player, lives),
+            Command::StartQuestionRound => self.start_question_round(),
+            Command::AskQuestion { player, question } => self.ask_question(player, question),
+            Command::JudgeAnswer { question, correct } => self.judge_answer(question, correct),
+            Command::SkipQuestion { question } => self.skip_question(question),
+            Command::StartVoting {
+                creator,
+                deadline,
+                anonymous,
+            } => self.start_voting(creator, deadline, anonymous),
+            Command::SetVotingDeadline { deadline } => self.set_voting_deadline(deadline),
+            Command::CancelVoting => self.cancel_voting(),
+            Command::CastVote { voter, target } => self.cast_vote(voter, target),
+            Command::WithdrawVote { voter } => self.withdraw_vote(voter),
+            Command::CastAudienceVote { voter, target } => self.cast_audience_vote(voter, target),
+            Command::CastTwitchVote { viewer, target } => self.cast_twitch_vote(viewer, target),
+            Command::SetAudienceVote { audience_vote } => self.set_audience_vote(audience_vote),
+            Command::SetGhostVote { ghost_vote } => self.set_ghost_vote(ghost_vote),
+            Command::SetVoteRules { vote_rules } => self.set_vote_rules(vote_rules),
+            Command::SetVoteChanges { vote_changes } => self.set_vote_changes(vote_changes),
+            Command::EndVoting => self.end_voting(),
+            Command::SetTieBreak { tie_break } => self.set_tie_break(tie_break),
+            Command::DecideTie { loser } => self.decide_tie(loser),
+            Command::SetFinaleRules { finale_rules } => self.set_finale_rules(finale_rules),
+        }?;
+
+        let at = Utc::now();
+        self.game.log.extend(events.iter().map(|event| LogEntry {
+            at,
+            event: event.clone(),
+        }));
+
+        Ok(events)
+    }
+
+    fn join(&mut self, player: UserId, lives: i32) -> Result<Vec<Event>, EngineError> {
+        if matches!(self.phase, Phase::Voting | Phase::Finale) {
+            return Err(EngineError::InvalidPhase(self.phase));
+        }
+        if self.game.lobby_closed {
+            return Err(EngineError::LobbyClosed);
+        }
+        if let Some(max_players) = self.game.max_players {
+            // joining twice is reported by `add_player` instead
+            if !self.game.contains_player(player) && self.game.members.len() >= max_players as usize
+            {
+                return Err(EngineError::LobbyFull(max_players));
+            }
+        }
+
+        self.game.add_player(player, lives)?;
+        Ok(vec![Event::PlayerJoined { player, lives }])
+    }
+
+    fn set_max_players(&mut self, max_players: Option<u32>) -> Result<Vec<Event>, EngineError> {
+        if self.phase != Phase::Lobby {
+            return Err(EngineError::InvalidPhase(self.phase));
+        }
+
+        self.game.max_players = max_players;
+        Ok(vec![Event::MaxPlayersChanged { max_players }])
+    }
+
+    fn close_lobby(&mut self) -> Result<Vec<Event>, EngineError> {
+        if self.game.lobby_closed {
+            return Err(EngineError::LobbyClosed);
+        }
+
+        self.game.lobby_closed = true;
+        Ok(vec![Event::LobbyClosed])
+    }
+
+    fn leave(&mut self, player: UserId) -> Result<Vec<Event>, EngineError> {
+        self.game.remove_player(player)?;
+
+        // votes by or for a player that left don't count anymore
+        if let Some(voting) = &mut self.voting {
+            voting
+                .map
+                .retain(|voter, target| *voter != player && *target != player);
+            voting.audience.retain(|_, target| *target != player);
+        }
+
+        let mut events = vec![Event::PlayerLeft { player }];
+
+        // the other finalist wins if one of them leaves
+        if let Some(finale) = &self.game.finale {
+            if self.phase == Phase::Finale && finale.players.contains(&player) {
+                let winner = finale
+                    .players
+                    .into_iter()
+                    .find(|finalist| *finalist != player);
+
+                events.push(self.finish(winner));
+            }
+        }
+
+        // like an elimination, a running voting is resolved first
+        if matches!(self.phase, Phase::QuestionRound | Phase::Resolution) {
+            events.extend(self.check_for_game_end());
+        }
+
+        Ok(events)
+    }
+
+    fn set_lives(&mut self, player: UserId, lives: i32) -> Result<Vec<Event>, EngineError> {
+        let was_alive = self.game.is_player_alive(player)?;
+        self.game.set_player_health(player, lives)?;
+
+        let mut events = vec![Event::LivesChanged { player, lives }];
+        if was_alive && lives <= 0 {
+            self.record_elimination(player, self.game.round);
+            events.push(Event::PlayerEliminated { player });
+        } else if lives > 0 {
+            self.game
+                .eliminations
+                .retain(|elimination| elimination.player != player);
+        }
+
+        // a running voting is resolved first, the lobby hasn't started yet
+        if matches!(
+            self.phase,
+            Phase::QuestionRound | Phase::Resolution | Phase::Finale
+        ) {
+            events.extend(self.check_for_game_end());
+        }
+
+        Ok(events)
+    }
+
+    fn start_question_round(&mut self) -> Result<Vec<Event>, EngineError> {
+        match self.phase {
+            Phase::Lobby | Phase::Resolution => (),
+            Phase::Voting => return Err(EngineError::VotingAlreadyActive),
+            phase => return Err(EngineError::InvalidPhase(phase)),
+        }
+        self.ensure_no_pending_tie()?;
+        self.ensure_enough_players()?;
+
+        self.phase = Phase::QuestionRound;
+        Ok(vec![Event::QuestionRoundStarted])
+    }
+
+    /// Starts the question round if there isn't one yet
+    fn ask_question(
+        &mut self,
+        player: UserId,
+        question: Question,
+    ) -> Result<Vec<Event>, EngineError> {
+        if !self.game.is_player_alive(player)? {
+            return Err(PlayerError::PlayerEliminated(player).into());
+        }
+
+        let mut events = match self.phase {
+            Phase::QuestionRound => Vec::new(),
+            Phase::Finale => {
+                self.ensure_finale_turn(player)?;
+                Vec::new()
+            },
+            _ => self.start_question_round()?,
+        };
+
+        let round = self.game.round;
+        self.game.asked_questions.push(AskedQuestion {
+            round,
+            player,
+            question: question.clone(),
+            answer: None,
+        });
+        events.push(Event::QuestionAsked {
+            round,
+            player,
+            question,
+        });
+
+        Ok(events)
+    }
+
+    /// A judged answer can be judged again, in case the moderator pressed the wrong button. That
+    /// isn't possible in the finale, where the answer decides who's next.
+    fn judge_answer(&mut self, index: usize, correct: bool) -> Result<Vec<Event>, EngineError> {
+        if !matches!(self.phase, Phase::QuestionRound | Phase::Finale) {
+            return Err(EngineError::InvalidPhase(self.phase));
+        }
+
+        let round = self.game.round;
+        let asked = self
+            .game
+            .asked_questions
+            .get_mut(index)
+            .ok_or(EngineError::UnknownQuestion(index))?;
+        let finale_on_turn = self.game.finale.as_ref().map(Finale::on_turn);
+        if asked.round != round
+            || asked.answer == Some(Answer::Skipped)
+            || (self.phase == Phase::Finale
+                && (asked.answer.is_some() || Some(asked.player) != finale_on_turn))
+        {
+            return Err(EngineError::QuestionClosed(index));
+        }
+
+        let answer = if correct {
+            Answer::Correct
+        } else {
+            Answer::Wrong
+        };
+        asked.answer = Some(answer);
+        let player = asked.player;
+
+        let mut events = vec![Event::AnswerJudged {
+            round,
+            player,
+            answer,
+        }];
+        if let Some(finale) = &mut self.game.finale {
+            finale.answers.push(correct);
+
+            if let Some(winner) = finale.winner() {
+                events.push(self.finish(Some(winner)));
+            }
+        }
+
+        Ok(events)
+    }
+
+    /// Questions nobody judged until now don't count as right or wrong
+    fn skip_open_questions(&mut self) {
+        for asked in &mut self.game.asked_questions {
+            if asked.round == self.game.round && asked.answer.is_none() {
+                asked.answer = Some(Answer::Skipped);
+            }
+        }
+    }
+
+    /// A skipped question of the finale doesn't count, the finalist on turn gets another one
+    fn skip_question(&mut self, index: usize) -> Result<Vec<Event>, EngineError> {
+        if !matches!(self.phase, Phase::QuestionRound | Phase::Finale) {
+            return Err(EngineError::InvalidPhase(self.phase));
+        }
+
+        let round = self.game.round;
+        let asked = self
+            .game
+            .asked_questions
+            .get_mut(index)
+            .ok_or(EngineError::UnknownQuestion(index))?;
+        if asked.round != round || asked.answer.is_some() {
+            return Err(EngineError::QuestionClosed(index));
+        }
+
+        asked.answer = Some(Answer::Skipped);
+        Ok(vec![Event::AnswerJudged {
+            round,
+            player: asked.player,
+            answer: Answer::Skipped,
+        }])
+    }
+
+    /// Only the finalist on turn can be asked, and only once their last question was judged
+    fn ensure_finale_turn(&self, player: UserId) -> Result<(), EngineError> {
+        let finale = self.game.finale.as_ref().expect("Expected a finale");
+
+        if self.open_question().is_some() {
+            return Err(EngineError::FinaleQuestionOpen);
+        }
+        if finale.on_turn() != player {
+            return Err(EngineError::NotOnTurn(finale.on_turn()));
+        }
+
+        Ok(())
+    }
+
+    fn start_voting(
+        &mut self,
+        creator: UserId,
+        deadline: Option<DateTime<Utc>>,
+        anonymous: bool,
+    ) -> Result<Vec<Event>, EngineError> {
+        match self.phase {
+            Phase::Voting => return Err(EngineError::VotingAlreadyActive),
+            Phase::Finale => return Err(EngineError::InvalidPhase(self.phase)),
+            _ => (),
+        }
+        self.ensure_no_pending_tie()?;
+        self.ensure_enough_players()?;
+
+        self.skip_open_questions();
+        self.open_voting(creator, deadline, None, anonymous);
+        Ok(vec![Event::VotingStarted {
+            creator,
+            deadline,
+            anonymous,
+        }])
+    }
+
+    fn open_voting(
+        &mut self,
+        creator: UserId,
+        deadline: Option<DateTime<Utc>>,
+        candidates: Option<Vec<UserId>>,
+        anonymous: bool,
+    ) {
+        self.votings_started += 1;
+        self.voting = Some(Voting {
+            id: self.votings_started,
+            creator,
+            map: HashMap::new(),
+            deadline,
+            candidates,
+            audience: HashMap::new(),
+            twitch: HashMap::new(),
+            anonymous,
+            vote_changes: self.game.vote_changes,
+            changes: Vec::new(),
+        });
+        self.phase = Phase::Voting;
+    }
+
+    fn set_voting_deadline(
+        &mut self,
+        deadline: Option<DateTime<Utc>>,
+    ) -> Result<Vec<Event>, EngineError> {
+        let voting = self.voting.as_mut().ok_or(EngineError::NoActiveVoting)?;
+        voting.deadline = deadline;
+
+        Ok(vec![Event::VotingDeadlineChanged { deadline }])
+    }
+
+    fn cancel_voting(&mut self) -> Result<Vec<Event>, EngineError> {
+        self.voting.take().ok_or(EngineError::NoActiveVoting)?;
+        self.phase = Phase::QuestionRound;
+
+        Ok(vec![Event::VotingCancelled])
+    }
+
+    fn cast_vote(&mut self, voter: UserId, target: UserId) -> Result<Vec<Event>, EngineError> {
+        let voting = self.voting.as_mut().ok_or(EngineError::NoActiveVoting)?;
+
+        if !self.game.can_vote(voter)? {
+            return Err(PlayerError::PlayerEliminated(voter).into());
+        }
+        let previous = voting.map.get(&voter).copied();
+        if previous.is_some() && voting.vote_changes.policy == VoteChangePolicy::Locked {
+            return Err(EngineError::AlreadyVoted(voter));
+        }
+        Self::ensure_can_be_voted(&self.game, voting, target)?;
+        self.game
+            .vote_rules
+            .check(&self.game, voter, target, voting.candidates.is_some())?;
+
+        voting.map.insert(voter, target);
+
+        match previous {
+            Some(from) if from != target => {
+                let change = VoteChange {
+                    voter,
+                    from,
+                    to: Some(target),
+                };
+                voting.changes.push(change);
+                Ok(vec![Event::VoteChanged {
+                    voter,
+                    from,
+                    to: Some(target),
+                }])
+            },
+            // voting for the same player again doesn't change anything
+            Some(_) => Ok(Vec::new()),
+            None => Ok(vec![Event::VoteCast { voter, target }]),
+        }
+    }
+
+    fn withdraw_vote(&mut self, voter: UserId) -> Result<Vec<Event>, EngineError> {
+        let voting = self.voting.as_mut().ok_or(EngineError::NoActiveVoting)?;
+
+        if voting.vote_changes.policy != VoteChangePolicy::ChangeOrWithdraw {
+            return Err(EngineError::WithdrawNotAllowed);
+        }
+        let from = voting
+            .map
+            .remove(&voter)
+            .ok_or(EngineError::NotVoted(voter))?;
+
+        voting.changes.push(VoteChange {
+            voter,
+            from,
+            to: None,
+        });
+        Ok(vec![Event::VoteChanged {
+            voter,
+            from,
+            to: None,
+        }])
+    }
+
+    fn cast_audience_vote(
+        &mut self,
+        voter: UserId,
+        target: UserId,
+    ) -> Result<Vec<Event>, EngineError> {
+        let voting = self.voting.as_mut().ok_or(EngineError::NoActiveVoting)?;
+
+        if !self.game.audience_vote.enabled {
+            return Err(EngineError::AudienceVoteDisabled);
+        }
+        // eliminated players still belong to the game
+        if self.game.contains_player(voter) {
+            return Err(EngineError::PlayerInAudience(voter));
+        }
+        if voting.audience.contains_key(&voter) {
+            return Err(EngineError::AlreadyVoted(voter));
+        }
+        Self::ensure_can_be_voted(&self.game, voting, target)?;
+
+        voting.audience.insert(voter, target);
+        Ok(vec![Event::AudienceVoteCast { voter, target }])
+    }
+
+    fn cast_twitch_vote(
+        &mut self,
+        viewer: String,
+        target: UserId,
+    ) -> Result<Vec<Event>, EngineError> {
+        let voting = self.voting.as_mut().ok_or(EngineError::NoActiveVoting)?;
+        Self::ensure_can_be_voted(&self.game, voting, target)?;
+
+        if voting.twitch.insert(viewer.clone(), target) == Some(target) {
+            return Ok(Vec::new());
+        }
+        Ok(vec![Event::TwitchVoteCast { viewer, target }])
+    }
+
+    fn ensure_can_be_voted(
+        game: &Game,
+        voting: &Voting,
+        target: UserId,
+    ) -> Result<(), EngineError> {
+        if game.is_player_dead(target)? {
+            return Err(PlayerError::PlayerEliminated(target).into());
+        }
+        if voting
+            .candidates
+            .as_ref()
+            .is_some_and(|candidates| !candidates.contains(&target))
+        {
+            return Err(EngineError::NotACandidate(target));
+        }
+
+        Ok(())
+    }
+
+    fn set_vote_changes(&mut self, vote_changes: VoteChanges) -> Result<Vec<Event>, EngineError> {
+        self.game.vote_changes = vote_changes;
+        Ok(vec![Event::VoteChangesChanged { vote_changes }])
+    }
+
+    fn set_vote_rules(&mut self, vote_rules: VoteRules) -> Result<Vec<Event>, EngineError> {
+        self.game.vote_rules = vote_rules;
+        Ok(vec![Event::VoteRulesChanged { vote_rules }])
+    }
+
+    fn set_ghost_vote(&mut self, ghost_vote: GhostVote) -> Result<Vec<Event>, EngineError> {
+        self.game.ghost_vote = ghost_vote;
+        Ok(vec![Event::GhostVoteChanged { ghost_vote }])
+    }
+
+    fn set_audience_vote(
+        &mut self,
+        audience_vote: AudienceVote,
+    ) -> Result<Vec<Event>, EngineError> {
+        self.game.audience_vote = audience_vote;
+        Ok(vec![Event::AudienceVoteChanged { audience_vote }])
+    }
+
+    fn end_voting(&mut self) -> Result<Vec<Event>, EngineError> {
+        let voting = self.voting.take().ok_or(EngineError::NoActiveVoting)?;
+
+        let counts = sum_up_votes(&voting.map, &self.game);
+        let audience_counts = sum_up_votes(&voting.audience, &self.game);
+        let twitch_counts =
+            voting
+                .twitch
+                .values()
+                .fold(MemberVoteCount::new(), |mut counts, target| {
+                    *counts.entry(*target).or_default() += 1;
+                    counts
+                });
+        let outcome = decide_winner(&weigh_audience_votes(
+            &counts,
+            &audience_counts,
+            self.game.audience_vote.votes_per_player_vote,
+        ));
+
+        self.game.rounds.push(RoundRecord {
+            round: self.game.round,
+            resolved_at: Utc::now(),
+            runoff: voting.candidates.is_some(),
+            anonymous: voting.anonymous,
+            votes: voting.map.clone(),
+            counts: counts.clone(),
+            audience_counts: audience_counts.clone(),
+            twitch_counts: twitch_counts.clone(),
+            changes: voting.changes.clone(),
+            outcome: outcome.clone(),
+            lost_life: Vec::new(),
+        });
+
+        let mut events = vec![Event::VotingResolved {
+            votes: voting.map.clone(),
+            anonymous: voting.anonymous,
+            counts,
+            audience_counts,
+            twitch_counts,
+            changes: if voting.vote_changes.show {
+                voting.changes.clone()
+            } else {
+                Vec::new()
+            },
+            outcome: outcome.clone(),
+        }];
+        self.phase = Phase::Resolution;
+
+        let losers = match outcome {
+            VoteOutcome::ClearWinner { user, .. } => vec![user],
+            VoteOutcome::NoClearWinner {
+                members_with_equal_votes,
+                ..
+            } => {
+                // a runoff that ends in another tie isn't broken again, otherwise it might never end
+                if voting.candidates.is_some() {
+                    Vec::new()
+                } else {
+                    self.break_tie(&voting, members_with_equal_votes, &mut events)
+                }
+            },
+            VoteOutcome::NoVotes => Vec::new(),
+        };
+
+        for loser in &losers {
+            self.take_life(*loser, self.game.round, &mut events)?;
+        }
+        self.record_lost_lives(&losers);
+
+        // a runoff still belongs to the current round
+        if self.phase == Phase::Resolution {
+            self.game.round += 1;
+            events.extend(self.check_for_game_end());
+        }
+
+        Ok(events)
+    }
+
+    /// Returns the players that lose a life right away
+    fn break_tie(
+        &mut self,
+        voting: &Voting,
+        tied: Vec<UserId>,
+        events: &mut Vec<Event>,
+    ) -> Vec<UserId> {
+        let tie_break = self.game.tie_break;
+
+        let losers = match tie_break {
+            TieBreak::Nobody => Vec::new(),
+            TieBreak::AllLoseLife => tied,
+            TieBreak::Random => tied
+                .choose(&mut rand::thread_rng())
+                .copied()
+                .into_iter()
+                .collect(),
+            TieBreak::FewestLives => {
+                let lives_of = |player: &UserId| self.game.members.get(player).copied();
+                let fewest_lives = tied.iter().filter_map(lives_of).min();
+                let with_fewest_lives = tied
+                    .iter()
+                    .copied()
+                    .filter(|player| lives_of(player) == fewest_lives)
+                    .collect::<Vec<_>>();
+
+                // still a tie if they all have the same amount of lives
+                if with_fewest_lives.len() == 1 {
+                    with_fewest_lives
+                } else {
+                    Vec::new()
+                }
+            },
+            TieBreak::Moderator => {
+                self.pending_tie = Some(tied.clone());
+                events.push(Event::TieBreakPending { candidates: tied });
+                return Vec::new();
+            },
+            TieBreak::Runoff => {
+                self.open_voting(voting.creator, None, Some(tied.clone()), voting.anonymous);
+                events.push(Event::RunoffStarted { candidates: tied });
+                return Vec::new();
+            },
+        };
+
+        events.push(Event::TieBroken {
+            tie_break,
+            losers: losers.clone(),
+        });
+        losers
+    }
+
+    fn take_life(
+        &mut self,
+        player: UserId,
+        round: u32,
+        events: &mut Vec<Event>,
+    ) -> Result<(), EngineError> {
+        let lives_left = self
+            .game
+            .members
+            .get(&player)
+            .map(|lives| lives - 1)
+            .ok_or(PlayerError::PlayerNotInGame(player))?;
+        self.game.set_player_health(player, lives_left)?;
+
+        events.push(Event::LifeLost { player, lives_left });
+
+        // check if the member that lost a life 'died' this round
+        if self.game.is_player_dead(player)? {
+            self.record_elimination(player, round);
+            events.push(Event::PlayerEliminated { player });
+        }
+
+        Ok(())
+    }
+
+    fn set_tie_break(&mut self, tie_break: TieBreak) -> Result<Vec<Event>, EngineError> {
+        self.game.tie_break = tie_break;
+        Ok(vec![Event::TieBreakChanged { tie_break }])
+    }
+
+    fn decide_tie(&mut self, loser: UserId) -> Result<Vec<Event>, EngineError> {
+        let candidates = self.pending_tie.as_ref().ok_or(EngineError::NoPendingTie)?;
+        if !candidates.contains(&loser) {
+            return Err(EngineError::NotACandidate(loser));
+        }
+        // the candidate might have left or been eliminated since, the tie stays open then
+        if !self.game.is_player_alive(loser)? {
+            return Err(PlayerError::PlayerEliminated(loser).into());
+        }
+
+        let mut events = vec![Event::TieBroken {
+            tie_break: TieBreak::Moderator,
+            losers: vec![loser],
+        }];
+        // the voting already moved on to the next round
+        let round = self
+            .game
+            .rounds
+            .last()
+            .map_or(self.game.round, |record| record.round);
+        self.take_life(loser, round, &mut events)?;
+        self.pending_tie = None;
+        self.record_lost_lives(&[loser]);
+        events.extend(self.check_for_game_end());
+
+        Ok(events)
+    }
+
+    fn record_elimination(&mut self, player: UserId, round: u32) {
+        self.game.eliminations.push(Elimination {
+            player,
+            round,
+            at: Utc::now(),
+        });
+    }
+
+    /// Adds the players to the latest [RoundRecord]
+    fn record_lost_lives(&mut self, players: &[UserId]) {
+        if let Some(record) = self.game.rounds.last_mut() {
+            record.lost_life.extend_from_slice(players);
+        }
+    }
+
+    fn set_finale_rules(&mut self, finale_rules: FinaleRules) -> Result<Vec<Event>, EngineError> {
+        if self.game.finale.is_some() {
+            return Err(EngineError::InvalidPhase(self.phase));
+        }
+
+        self.game.finale_rules = finale_rules;
+        Ok(vec![Event::FinaleRulesChanged { finale_rules }])
+    }
+
+    /// Starts the finale once two players are left alive, or finishes the game once one or no
+    /// player is left. A game that started with two players has no finale.
+    fn check_for_game_end(&mut self) -> Option<Event> {
+        // the tie has to be decided first
+        if self.pending_tie.is_some() {
+            return None;
+        }
+        let mut living_players = self.living_players().collect::<Vec<_>>();
+
+        if let [first, second] = living_players[..] {
+            if self.phase == Phase::Finale
+                || self.game.members.len() <= 2
+                || !self.game.finale_rules.enabled
+            {
+                return None;
+            }
+
+            // the player with more lives left starts, a draw is decided by the user id
+            let lives_of = |player: UserId| self.game.members.get(&player).copied();
+            let players = if lives_of(second) > lives_of(first)
+                || (lives_of(second) == lives_of(first) && second < first)
+            {
+                [second, first]
+            } else {
+                [first, second]
+            };
+            let questions_per_player = self.game.finale_rules.questions_per_player;
+
+            // a question of the regular game must not count as an answer of the finale
+            self.skip_open_questions();
+            self.game.finale = Some(Finale::new(players, questions_per_player));
+            self.phase = Phase::Finale;
+            return Some(Event::FinaleStarted {
+                players,
+                questions_per_player,
+            });
+        }
+        if living_players.len() > 1 {
+            return None;
+        }
+        let winner = living_players.pop();
+
+        Some(self.finish(winner))
+    }
+
+    /// Ends the game, nothing can be changed afterwards
+    fn finish(&mut self, winner: Option<UserId>) -> Event {
+        self.voting = None;
+        self.phase = Phase::Finished;
+
+        Event::GameFinished {
+            winner,
+            standings: self.game.standings(),
+        }
+    }
+
+    fn ensure_no_pending_tie(&self) -> Result<(), EngineError> {
+        if self.pending_tie.is_some() {
+            Err(EngineError::TieBreakPending)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn ensure_enough_players(&self) -> Result<(), EngineError> {
+        if self.living_players().count() < 2 {
+            Err(EngineError::NotEnoughPlayers)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Counts the votes per player.
+///
+/// Votes of eliminated players are weighed according to [Game::ghost_vote], everyone else (including
+/// the audience) has a full vote. A voter that was eliminated after voting counts as eliminated.
+pub fn sum_up_votes(
+    member_to_member_votes: &HashMap<UserId, UserId>,
+    game: &Game,
+) -> MemberVoteCount {
+    let mut votes = HashMap::new();
+    let mut ghost_votes = HashMap::new();
+
+    for (voter, voted) in member_to_member_votes {
+        let counts = if game.is_player_dead(*voter).unwrap_or(false) {
+            &mut ghost_votes
+        } else {
+            &mut votes
+        };
+        counts
+            .entry(*voted)
+            .and_modify(|num| *num += 1)
+            .or_insert(1);
+    }
+
+    if !game.ghosts_can_vote() {
+        return votes;
+    }
+    let votes_per_player_vote = match game.ghost_vote {
+        GhostVote::Reduced => GHOST_VOTES_PER_PLAYER_VOTE,
+        _ => 1,
+    };
+    for (voted, num) in ghost_votes {
+        let num = num / votes_per_player_vote;
+        if num > 0 {
+            *votes.entry(voted).or_insert(0) += num;
+        }
+    }
+
+    votes
+}
+
+/// Adds the audience votes to the votes of the players.
+///
+/// Every `votes_per_player_vote` audience votes for a player count as one vote, the rest is dropped.
+pub fn weigh_audience_votes(
+    counts: &MemberVoteCount,
+    audience_counts: &MemberVoteCount,
+    votes_per_player_vote: Option<u32>,
+) -> MemberVoteCount {
+    let mut weighted = counts.clone();
+
+    let Some(votes_per_player_vote) = votes_per_player_vote.filter(|votes| *votes > 0) else {
+        return weighted;
+    };
+
+    for (player, audience_votes) in audience_counts {
+        let votes = audience_votes / votes_per_player_vote as i32;
+        if votes > 0 {
+            *weighted.entry(*player).or_insert(0) += votes;
+        }
+    }
+
+    weighted
+}
+
+pub fn decide_winner(votes: &MemberVoteCount) -> VoteOutcome {
+    let Some(max_vote_count) = votes.values().max().copied() else {
+        return VoteOutcome::NoVotes;
+    };
+
+    let mut members_with_equal_votes = votes
+        .iter()
+        .filter(|(_, num)| **num == max_vote_count)
+        .map(|(user, _)| *user)
+        .collect::<Vec<_>>();
+
+    if members_with_equal_votes.len() > 1 {
+        members_with_equal_votes.sort();
+        VoteOutcome::NoClearWinner {
+            members_with_equal_votes,
+            max_vote_count,
+        }
+    } else {
+        VoteOutcome::ClearWinner {
+            user: members_with_equal_votes[0],
+            num_votes: max_vote_count,
+        }
+    }
+}
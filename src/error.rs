@@ -3,7 +3,15 @@ use poise::{
     CreateReply,
 };
 
-use crate::{Context, Error, FrameworkError, IntoAppContext};
+use crate::{
+    guild_locale,
+    i18n::localize_error,
+    t,
+    Context,
+    Error,
+    FrameworkError,
+    IntoAppContext,
+};
 
 pub fn error_embed(description: impl Into<String>) -> CreateEmbed {
     CreateEmbed::default()
@@ -39,11 +47,12 @@ pub async fn handle_command_error(
     err: Error,
     ctx: Context<'_>,
 ) -> Result<(), serenity_prelude::Error> {
+    let locale = guild_locale(ctx).await;
     ctx.send(
         CreateReply::default().embed(
             CreateEmbed::default()
-                .title("Fehler")
-                .description(err.to_string())
+                .title(t!(locale, "errors.title"))
+                .description(localize_error(err.as_ref(), locale))
                 .color(Color::DARK_RED),
         ),
     )
@@ -56,11 +65,12 @@ pub async fn handle_command_check_error(
     err: Error,
     ctx: Context<'_>,
 ) -> Result<(), serenity_prelude::Error> {
+    let locale = guild_locale(ctx).await;
     ctx.send(
         CreateReply::default().embed(
             CreateEmbed::default()
-                .title("Fehler")
-                .description(err.to_string())
+                .title(t!(locale, "errors.title"))
+                .description(localize_error(err.as_ref(), locale))
                 .color(Color::DARK_RED),
         ),
     )
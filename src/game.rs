@@ -1,25 +1,457 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, fmt};
 
-use poise::serenity_prelude::{Member, Mentionable, UserId};
+use chrono::{DateTime, Utc};
+use poise::serenity_prelude::{ChannelId, Mentionable, UserId};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    engine::{LogEntry, MemberVoteCount, VoteOutcome},
+    i18n::{Locale, Localize},
+    questions::Question,
+    rules::VoteRules,
+    t,
+};
 
 #[non_exhaustive]
 #[derive(Debug, thiserror::Error)]
 pub enum PlayerError {
-    #[error("{} ist bereits im Spiel.", _0.mention())]
     PlayerAlreadyAdded(UserId),
-
-    #[error("{} ist nicht im Spiel.", _0.mention())]
     PlayerNotInGame(UserId),
+    PlayerEliminated(UserId),
+}
+
+impl Localize for PlayerError {
+    fn localize(&self, locale: Locale) -> String {
+        match self {
+            PlayerError::PlayerAlreadyAdded(player) => {
+                t!(
+                    locale,
+                    "errors.player.already_added",
+                    player = player.mention()
+                )
+            },
+            PlayerError::PlayerNotInGame(player) => {
+                t!(
+                    locale,
+                    "errors.player.not_in_game",
+                    player = player.mention()
+                )
+            },
+            PlayerError::PlayerEliminated(player) => {
+                t!(
+                    locale,
+                    "errors.player.eliminated",
+                    player = player.mention()
+                )
+            },
+        }
+    }
 }
 
+impl fmt::Display for PlayerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.localize(Locale::default()))
+    }
+}
+
+/// What happens if several players have the most votes
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, poise::ChoiceParameter,
+)]
+pub enum TieBreak {
+    #[default]
+    #[name = "Niemand verliert ein Leben"]
+    Nobody,
+    #[name = "Der Moderator entscheidet"]
+    Moderator,
+    #[name = "Stichwahl zwischen den Gleichstehenden"]
+    Runoff,
+    #[name = "Alle Gleichstehenden verlieren ein Leben"]
+    AllLoseLife,
+    #[name = "Zufall"]
+    Random,
+    #[name = "Wer weniger Leben hat, verliert ein Leben"]
+    FewestLives,
+}
+
+impl Localize for TieBreak {
+    fn localize(&self, locale: Locale) -> String {
+        t!(
+            locale,
+            match self {
+                TieBreak::Nobody => "choices.tie_break.nobody",
+                TieBreak::Moderator => "choices.tie_break.moderator",
+                TieBreak::Runoff => "choices.tie_break.runoff",
+                TieBreak::AllLoseLife => "choices.tie_break.all_lose_life",
+                TieBreak::Random => "choices.tie_break.random",
+                TieBreak::FewestLives => "choices.tie_break.fewest_lives",
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Game {
-    pub creator: Member,
-    pub moderator: Member,
+    pub creator: UserId,
+    pub moderator: UserId,
+    // the channel the game was started in
+    pub channel_id: ChannelId,
     // user id to member's lives
     pub members: HashMap<UserId, i32>,
+    // players whose lives ran out, oldest first
+    #[serde(default)]
+    pub eliminations: Vec<Elimination>,
+    #[serde(default)]
+    pub tie_break: TieBreak,
+    #[serde(default)]
+    pub vote_rules: VoteRules,
+    #[serde(default)]
+    pub ghost_vote: GhostVote,
+    // copied to every new voting
+    #[serde(default)]
+    pub vote_changes: VoteChanges,
+    #[serde(default)]
+    pub audience_vote: AudienceVote,
+    #[serde(default)]
+    pub finale_rules: FinaleRules,
+    // `Some` once the last two players are in the finale
+    #[serde(default)]
+    pub finale: Option<Finale>,
+    #[serde(default)]
+    pub max_players: Option<u32>,
+    // nobody can join anymore once the moderator closed the lobby
+    #[serde(default)]
+    pub lobby_closed: bool,
+    // starts at 1 and counts up with every resolved voting
+    #[serde(default = "first_round")]
+    pub round: u32,
+    #[serde(default)]
+    pub asked_questions: Vec<AskedQuestion>,
+    // every resolved voting, oldest first
+    #[serde(default)]
+    pub rounds: Vec<RoundRecord>,
+    // every event of the game, oldest first
+    #[serde(default)]
+    pub log: Vec<LogEntry>,
+}
+
+pub fn first_round() -> u32 {
+    1
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AskedQuestion {
+    pub round: u32,
+    pub player: UserId,
+    pub question: Question,
+    // `None` until the moderator judged the answer
+    #[serde(default)]
+    pub answer: Option<Answer>,
+}
+
+/// Whether a vote can be taken back before the voting ends
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, poise::ChoiceParameter,
+)]
+pub enum VoteChangePolicy {
+    #[default]
+    #[name = "Votes sind endgültig"]
+    Locked,
+    #[name = "Votes können geändert werden"]
+    Change,
+    #[name = "Votes können geändert und zurückgezogen werden"]
+    ChangeOrWithdraw,
+}
+
+impl Localize for VoteChangePolicy {
+    fn localize(&self, locale: Locale) -> String {
+        t!(
+            locale,
+            match self {
+                VoteChangePolicy::Locked => "choices.vote_change_policy.locked",
+                VoteChangePolicy::Change => "choices.vote_change_policy.change",
+                VoteChangePolicy::ChangeOrWithdraw => {
+                    "choices.vote_change_policy.change_or_withdraw"
+                },
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VoteChanges {
+    pub policy: VoteChangePolicy,
+    // whether the result of the voting lists who changed their vote
+    pub show: bool,
+}
+
+/// `to` is `None` if the vote was withdrawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VoteChange {
+    pub voter: UserId,
+    pub from: UserId,
+    pub to: Option<UserId>,
+}
+
+/// Whether players with no lives left can still vote
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, poise::ChoiceParameter,
+)]
+pub enum GhostVote {
+    #[default]
+    #[name = "Ausgeschiedene können nicht voten"]
+    Nobody,
+    #[name = "Ausgeschiedene voten ganz normal"]
+    Full,
+    #[name = "Zwei Votes von Ausgeschiedenen zählen wie einer"]
+    Reduced,
+    #[name = "Ausgeschiedene voten nur in der letzten Abstimmung"]
+    FinalOnly,
+}
+
+impl Localize for GhostVote {
+    fn localize(&self, locale: Locale) -> String {
+        t!(
+            locale,
+            match self {
+                GhostVote::Nobody => "choices.ghost_vote.nobody",
+                GhostVote::Full => "choices.ghost_vote.full",
+                GhostVote::Reduced => "choices.ghost_vote.reduced",
+                GhostVote::FinalOnly => "choices.ghost_vote.final_only",
+            }
+        )
+    }
+}
+
+/// How many ghost votes for the same player make up one vote, see [GhostVote::Reduced]
+pub const GHOST_VOTES_PER_PLAYER_VOTE: i32 = 2;
+
+/// Lets people that don't play vote as well, their votes are counted separately
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AudienceVote {
+    pub enabled: bool,
+    // this many audience votes count as one player vote, `None` if they are only shown
+    pub votes_per_player_vote: Option<u32>,
+}
+
+/// How many questions every finalist has to answer before it goes into sudden death
+pub const DEFAULT_FINALE_QUESTIONS: u32 = 5;
+
+/// Whether the last two players play a final duel instead of more votings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FinaleRules {
+    pub enabled: bool,
+    pub questions_per_player: u32,
+}
+
+impl Default for FinaleRules {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            questions_per_player: DEFAULT_FINALE_QUESTIONS,
+        }
+    }
+}
+
+/// The final duel. The finalists take turns answering questions, the first one starts.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Finale {
+    pub players: [UserId; 2],
+    pub questions_per_player: u32,
+    // whether the answers were correct, oldest first. The answers alternate between the players.
+    pub answers: Vec<bool>,
+}
+
+impl Finale {
+    pub fn new(players: [UserId; 2], questions_per_player: u32) -> Self {
+        Self {
+            players,
+            questions_per_player,
+            answers: Vec::new(),
+        }
+    }
+
+    /// The player that gets the next question
+    pub fn on_turn(&self) -> UserId {
+        self.players[self.answers.len() % 2]
+    }
+
+    /// The amount of answered questions and correct answers of a finalist
+    pub fn score(&self, player: UserId) -> (u32, u32) {
+        let Some(offset) = self.players.iter().position(|finalist| *finalist == player) else {
+            return (0, 0);
+        };
+
+        self.answers
+            .iter()
+            .skip(offset)
+            .step_by(2)
+            .fold((0, 0), |(answered, correct), answer| {
+                (answered + 1, correct + u32::from(*answer))
+            })
+    }
+
+    /// The winner is decided once the other finalist can't catch up anymore. After the regular
+    /// questions, every pair of questions is sudden death.
+    pub fn winner(&self) -> Option<UserId> {
+        let [(answered_0, correct_0), (answered_1, correct_1)] =
+            self.players.map(|player| self.score(player));
+        let regular = self.questions_per_player;
+
+        if answered_0 == answered_1 && answered_0 >= regular && correct_0 != correct_1 {
+            let leader = if correct_0 > correct_1 { 0 } else { 1 };
+            return Some(self.players[leader]);
+        }
+
+        // still in the regular questions, but the lead can't be caught up anymore
+        if answered_0 <= regular && answered_1 <= regular {
+            if correct_0 > correct_1 + (regular - answered_1) {
+                return Some(self.players[0]);
+            }
+            if correct_1 > correct_0 + (regular - answered_0) {
+                return Some(self.players[1]);
+            }
+        }
+
+        None
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Elimination {
+    pub player: UserId,
+    pub round: u32,
+    pub at: DateTime<Utc>,
+}
+
+/// A row of [Game::standings]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Standing {
+    // starts at 1
+    pub place: u32,
+    pub player: UserId,
+    pub lives: i32,
+    // the round the player was eliminated in, `None` if they're still alive
+    pub eliminated_in: Option<u32>,
+}
+
+/// A resolved voting. A runoff shares the round number with the voting it was started by.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoundRecord {
+    pub round: u32,
+    pub resolved_at: DateTime<Utc>,
+    pub runoff: bool,
+    #[serde(default)]
+    pub anonymous: bool,
+    // voter -> the one they voted for
+    pub votes: HashMap<UserId, UserId>,
+    pub counts: MemberVoteCount,
+    #[serde(default)]
+    pub audience_counts: MemberVoteCount,
+    #[serde(default)]
+    pub twitch_counts: MemberVoteCount,
+    #[serde(default)]
+    pub changes: Vec<VoteChange>,
+    pub outcome: VoteOutcome,
+    pub lost_life: Vec<UserId>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Answer {
+    Correct,
+    Wrong,
+    // the round ended before the answer was judged, or the moderator skipped it
+    Skipped,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AnswerStats {
+    pub asked: u32,
+    pub correct: u32,
+    pub wrong: u32,
+    pub skipped: u32,
 }
 
 impl Game {
+    /// The answers of a player, either of a single round or of the whole game
+    pub fn answer_stats(&self, player: UserId, round: Option<u32>) -> AnswerStats {
+        let mut stats = AnswerStats::default();
+
+        for asked in self.asked_questions.iter().filter(|asked| {
+            asked.player == player && round.is_none_or(|round| asked.round == round)
+        }) {
+            stats.asked += 1;
+            match asked.answer {
+                Some(Answer::Correct) => stats.correct += 1,
+                Some(Answer::Wrong) => stats.wrong += 1,
+                Some(Answer::Skipped) => stats.skipped += 1,
+                None => (),
+            }
+        }
+
+        stats
+    }
+
+    /// Every player from first to last place. Players that are still alive are ranked by their
+    /// lives with the winner of the finale on top, eliminated players by how long they lasted.
+    /// Players with the same lives or eliminated in the same round share a place.
+    pub fn standings(&self) -> Vec<Standing> {
+        let finale_winner = self.finale.as_ref().and_then(Finale::winner);
+        let eliminated_in = |player: UserId| {
+            self.eliminations
+                .iter()
+                .rev()
+                .find(|elimination| elimination.player == player)
+                .map(|elimination| elimination.round)
+        };
+
+        let mut players = self
+            .members
+            .iter()
+            .map(|(player, lives)| {
+                let eliminated_in = if *lives > 0 {
+                    None
+                } else {
+                    eliminated_in(*player)
+                };
+                (*player, *lives, eliminated_in)
+            })
+            .collect::<Vec<_>>();
+        // players that never had any lives come last
+        players.sort_by_key(|(player, lives, eliminated_in)| {
+            (
+                Some(*player) != finale_winner,
+                std::cmp::Reverse((*lives).max(0)),
+                std::cmp::Reverse(*eliminated_in),
+                *player,
+            )
+        });
+
+        let mut standings = Vec::<Standing>::with_capacity(players.len());
+        for (index, (player, lives, eliminated_in)) in players.into_iter().enumerate() {
+            let shares_place = standings.last().is_some_and(|previous| {
+                Some(player) != finale_winner
+                    && Some(previous.player) != finale_winner
+                    && previous.lives.max(0) == lives.max(0)
+                    && previous.eliminated_in == eliminated_in
+            });
+            let place = match standings.last() {
+                Some(previous) if shares_place => previous.place,
+                _ => index as u32 + 1,
+            };
+
+            standings.push(Standing {
+                place,
+                player,
+                lives,
+                eliminated_in,
+            });
+        }
+
+        standings
+    }
+
     pub fn contains_player(&self, player: UserId) -> bool {
         self.members.contains_key(&player)
     }
@@ -60,10 +492,58 @@ impl Game {
     pub fn is_player_alive(&self, player: UserId) -> Result<bool, PlayerError> {
         self.is_player_dead(player).map(|is_alive| !is_alive)
     }
+
+    /// Whether eliminated players can vote right now, see [GhostVote]
+    pub fn ghosts_can_vote(&self) -> bool {
+        match self.ghost_vote {
+            GhostVote::Nobody => false,
+            GhostVote::Full | GhostVote::Reduced => true,
+            GhostVote::FinalOnly => {
+                self.members.values().filter(|lives| **lives > 0).count()
+                    <= self.players_in_final_voting()
+            },
+        }
+    }
+
+    /// How many players are left alive in the last voting, the one that decides who reaches
+    /// the finale. Without a finale the voting goes on until only one player is left.
+    fn players_in_final_voting(&self) -> usize {
+        if self.finale_rules.enabled && self.members.len() > 2 {
+            3
+        } else {
+            2
+        }
+    }
+
+    /// Alive players can always vote, eliminated ones depending on [Game::ghost_vote]
+    pub fn can_vote(&self, player: UserId) -> Result<bool, PlayerError> {
+        Ok(self.is_player_alive(player)? || self.ghosts_can_vote())
+    }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Voting {
-    pub creator: Member,
-    // user id to member's lives
+    // counts up with every voting of a game, so an old voting can be told apart from a new one
+    pub id: u32,
+    pub creator: UserId,
+    // voter to voted user
     pub map: HashMap<UserId, UserId>,
+    // the voting is closed automatically once this is reached
+    pub deadline: Option<DateTime<Utc>>,
+    // only these players can be voted, used for runoffs
+    pub candidates: Option<Vec<UserId>>,
+    // voter to voted user, for everyone that isn't part of the game
+    #[serde(default)]
+    pub audience: HashMap<UserId, UserId>,
+    // Twitch username to voted user, see [crate::twitch]
+    #[serde(default)]
+    pub twitch: HashMap<String, UserId>,
+    // only the moderator gets to see who voted whom
+    #[serde(default)]
+    pub anonymous: bool,
+    #[serde(default)]
+    pub vote_changes: VoteChanges,
+    // every changed or withdrawn vote, oldest first
+    #[serde(default)]
+    pub changes: Vec<VoteChange>,
 }
@@ -0,0 +1,101 @@
+use std::io;
+
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+
+// a browser or Stream Deck plugin never sends more than this
+const MAX_HEADER_LINES: usize = 100;
+const MAX_BODY_SIZE: usize = 64 * 1024;
+
+/// Just enough of an HTTP/1.1 request for the overlay and the control API, both only listen
+/// locally
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    // everything after the `?`, without it
+    pub query: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    /// Reads the request line, the headers and a body with `Content-Length`
+    pub async fn read<S: tokio::io::AsyncRead + Unpin>(
+        stream: &mut BufReader<S>,
+    ) -> io::Result<Self> {
+        let mut request_line = String::new();
+        stream.read_line(&mut request_line).await?;
+
+        let mut parts = request_line.split_whitespace();
+        let method = parts.next().unwrap_or_default().to_owned();
+        let target = parts.next().unwrap_or("/");
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+
+        let mut headers = Vec::new();
+        for _ in 0..MAX_HEADER_LINES {
+            let mut header = String::new();
+            if stream.read_line(&mut header).await? == 0 || header.trim().is_empty() {
+                break;
+            }
+            if let Some((name, value)) = header.split_once(':') {
+                headers.push((name.trim().to_lowercase(), value.trim().to_owned()));
+            }
+        }
+
+        let mut request = Self {
+            method,
+            path: path.to_owned(),
+            query: query.to_owned(),
+            headers,
+            body: Vec::new(),
+        };
+
+        let length = request
+            .header("content-length")
+            .and_then(|length| length.parse::<usize>().ok())
+            .unwrap_or(0);
+        if length > MAX_BODY_SIZE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "request body too large",
+            ));
+        }
+        request.body = vec![0; length];
+        stream.read_exact(&mut request.body).await?;
+
+        Ok(request)
+    }
+
+    /// Header names are case-insensitive
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn query_param(&self, name: &str) -> Option<&str> {
+        self.query
+            .split('&')
+            .filter_map(|param| param.split_once('='))
+            .find(|(param, _)| *param == name)
+            .map(|(_, value)| value)
+    }
+}
+
+/// Writes a complete response and closes the connection
+pub async fn respond(
+    stream: &mut (impl AsyncWrite + Unpin),
+    status: &str,
+    content_type: &str,
+    body: &[u8],
+) -> io::Result<()> {
+    let head = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: \
+         close\r\n\r\n",
+        body.len()
+    );
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(body).await?;
+    stream.shutdown().await
+}
@@ -0,0 +1,200 @@
+use std::{collections::HashMap, fmt, sync::OnceLock};
+
+use serde::{Deserialize, Serialize};
+
+const GERMAN_CATALOG: &str = include_str!("../locales/de.toml");
+const ENGLISH_CATALOG: &str = include_str!("../locales/en.toml");
+
+/// The languages the bot speaks, chosen per guild with `/set-language`
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize, poise::ChoiceParameter,
+)]
+pub enum Locale {
+    #[default]
+    #[name = "Deutsch"]
+    German,
+    #[name = "English"]
+    English,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 2] = [Locale::German, Locale::English];
+
+    /// The Discord locales the slash commands are translated to
+    pub fn discord_locales(self) -> &'static [&'static str] {
+        match self {
+            Locale::German => &["de"],
+            Locale::English => &["en-US", "en-GB"],
+        }
+    }
+
+    fn source(self) -> &'static str {
+        match self {
+            Locale::German => GERMAN_CATALOG,
+            Locale::English => ENGLISH_CATALOG,
+        }
+    }
+}
+
+/// Every message of a locale, the nested tables of the TOML file joined with dots, e.g.
+/// `checks.no_active_game`
+pub type Catalog = HashMap<String, String>;
+
+pub fn catalog(locale: Locale) -> &'static Catalog {
+    static CATALOGS: OnceLock<HashMap<Locale, Catalog>> = OnceLock::new();
+
+    let catalogs = CATALOGS.get_or_init(|| {
+        Locale::ALL
+            .into_iter()
+            .map(|locale| {
+                let table = locale
+                    .source()
+                    .parse::<toml::Table>()
+                    .unwrap_or_else(|err| panic!("the {locale:?} catalog is invalid: {err}"));
+
+                let mut catalog = Catalog::new();
+                flatten("", table, &mut catalog);
+                (locale, catalog)
+            })
+            .collect()
+    });
+
+    &catalogs[&locale]
+}
+
+fn flatten(prefix: &str, table: toml::Table, catalog: &mut Catalog) {
+    for (key, value) in table {
+        let key = if prefix.is_empty() {
+            key
+        } else {
+            format!("{prefix}.{key}")
+        };
+
+        match value {
+            toml::Value::Table(table) => flatten(&key, table, catalog),
+            toml::Value::String(message) => {
+                catalog.insert(key, message);
+            },
+            value => panic!("`{key}` must be a string or a table, not {value}"),
+        }
+    }
+}
+
+/// Looks up a message. A message missing in a locale falls back to German, and to the key itself
+/// if it's missing there too.
+pub fn tr(locale: Locale, key: &str) -> &str {
+    catalog(locale)
+        .get(key)
+        .or_else(|| catalog(Locale::default()).get(key))
+        .map_or(key, String::as_str)
+}
+
+/// Like [tr], with every `{name}` in the message replaced by its argument
+pub fn tr_args(locale: Locale, key: &str, args: &[(&str, &(dyn fmt::Display + Sync))]) -> String {
+    let mut message = tr(locale, key).to_owned();
+    for (name, value) in args {
+        message = message.replace(&format!("{{{name}}}"), &value.to_string());
+    }
+    message
+}
+
+/// Looks up a message of the catalog, see [tr] and [tr_args]
+///
+/// ```ignore
+/// t!(locale, "vote.voted", player = ctx.author().mention())
+/// ```
+#[macro_export]
+macro_rules! t {
+    ($locale:expr, $key:expr $(,)?) => {
+        $crate::i18n::tr($locale, $key).to_owned()
+    };
+    ($locale:expr, $key:expr, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::i18n::tr_args(
+            $locale,
+            $key,
+            &[$((stringify!($name), &$value as &(dyn ::std::fmt::Display + Sync))),+],
+        )
+    };
+}
+
+/// Something that is shown to users in the language of their guild, like an error
+pub trait Localize {
+    fn localize(&self, locale: Locale) -> String;
+}
+
+/// The text of an error in the given locale. Errors of the engine are translated, everything else
+/// is expected to be written in the right language already.
+pub fn localize_error(err: &(dyn std::error::Error + 'static), locale: Locale) -> String {
+    use crate::{engine::EngineError, game::PlayerError, rules::VoteRuleError};
+
+    if let Some(err) = err.downcast_ref::<EngineError>() {
+        err.localize(locale)
+    } else if let Some(err) = err.downcast_ref::<PlayerError>() {
+        err.localize(locale)
+    } else if let Some(err) = err.downcast_ref::<VoteRuleError>() {
+        err.localize(locale)
+    } else {
+        err.to_string()
+    }
+}
+
+/// Adds the translations of the catalog to the slash commands, their parameters and choices.
+///
+/// German is what Discord shows by default. The keys are `commands.<command>.name`,
+/// `commands.<command>.description`, `commands.<command>.<parameter>.name` and
+/// `commands.<command>.<parameter>.description`. A name without a key stays as it is.
+/// Choices are matched by their German name against everything in `choices`.
+pub fn localize_commands<U, E>(commands: &mut [poise::Command<U, E>]) {
+    let german = catalog(Locale::German);
+    let choice_keys = german
+        .iter()
+        .filter(|(key, _)| key.starts_with("choices."))
+        .map(|(key, name)| (name.as_str(), key.as_str()))
+        .collect::<HashMap<_, _>>();
+
+    for command in commands {
+        let prefix = format!("commands.{}", command.name);
+        add_localizations(&format!("{prefix}.name"), &mut command.name_localizations);
+        localize(
+            &format!("{prefix}.description"),
+            &mut command.description,
+            &mut command.description_localizations,
+        );
+
+        for parameter in &mut command.parameters {
+            let prefix = format!("{prefix}.{}", parameter.name);
+
+            add_localizations(&format!("{prefix}.name"), &mut parameter.name_localizations);
+            localize(
+                &format!("{prefix}.description"),
+                &mut parameter.description,
+                &mut parameter.description_localizations,
+            );
+
+            for choice in &mut parameter.choices {
+                if let Some(key) = choice_keys.get(choice.name.as_str()) {
+                    add_localizations(key, &mut choice.localizations);
+                }
+            }
+        }
+    }
+}
+
+/// Sets the German message as the default and adds every locale to the localizations
+fn localize(key: &str, default: &mut Option<String>, localizations: &mut HashMap<String, String>) {
+    if let Some(german) = catalog(Locale::German).get(key) {
+        *default = Some(german.clone());
+    }
+    add_localizations(key, localizations);
+}
+
+fn add_localizations(key: &str, localizations: &mut HashMap<String, String>) {
+    for locale in Locale::ALL {
+        let Some(message) = catalog(locale).get(key) else {
+            continue;
+        };
+        for discord_locale in locale.discord_locales() {
+            localizations.insert((*discord_locale).to_owned(), message.clone());
+        }
+    }
+}
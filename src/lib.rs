@@ -1,13 +1,26 @@
+pub mod api;
 pub mod checks;
+pub mod config;
 pub mod error;
 pub mod models;
-use std::sync::Arc;
+pub mod overlay;
+use std::sync::{Arc, OnceLock};
 
-use data::Data;
+use config::GameConfig;
+use data::{Data, Session};
+use i18n::Locale;
 
 pub mod commands;
 pub mod data;
+pub mod engine;
 pub mod game;
+pub mod http;
+pub mod i18n;
+pub mod persistence;
+pub mod questions;
+pub mod rules;
+pub mod stats;
+pub mod twitch;
 
 pub type Error = Box<dyn std::error::Error + Send + Sync>;
 
@@ -19,6 +32,41 @@ pub type FrameworkError<'a> = poise::FrameworkError<'a, Arc<Data>, Error>;
 
 pub const DEFAULT_COLOR: u32 = 0x87CEEB;
 
+static EMBED_COLOR: OnceLock<u32> = OnceLock::new();
+
+/// The color of every embed, [DEFAULT_COLOR] unless the config says otherwise
+pub fn embed_color() -> u32 {
+    EMBED_COLOR.get().copied().unwrap_or(DEFAULT_COLOR)
+}
+
+/// Only the first call has an effect, the color is set once at startup
+pub fn set_embed_color(color: u32) {
+    let _ = EMBED_COLOR.set(color);
+}
+
+/// Resolves the [Session] of the guild the command was invoked in.
+///
+/// Every command is `guild_only`, so the guild ID is always set.
+pub async fn guild_session<'a>(ctx: impl Into<ContextEnum<'a>>) -> Arc<Session> {
+    let ctx = ctx.into();
+    ctx.data()
+        .session(ctx.guild_id().expect("guild ID should be set"))
+        .await
+}
+
+/// The language the guild of the command chose, see [Session::locale]
+pub async fn guild_locale<'a>(ctx: impl Into<ContextEnum<'a>>) -> Locale {
+    guild_session(ctx).await.locale().await
+}
+
+/// The game defaults of the guild of the command, see [Data::game_config]
+pub async fn guild_config<'a>(ctx: impl Into<ContextEnum<'a>>) -> GameConfig {
+    let ctx = ctx.into();
+    ctx.data()
+        .game_config(ctx.guild_id().expect("guild ID should be set"))
+        .await
+}
+
 pub trait IntoAppContext<'a> {
     fn into_app_context(self) -> Context<'a>;
 }
@@ -1,56 +1,187 @@
-use std::{collections::HashSet, sync::Arc};
+use std::{collections::HashSet, net::SocketAddr, sync::Arc};
 
 use ddf_bot::{
+    api::{self, ApiConfig, ControlApi},
     commands::{
+        audience::{audience_vote, set_audience_vote},
+        config::config as config_command,
+        export::export_game,
+        finale::set_finale,
         game::{add_user, remove_user, show_game, start_game},
+        ghost_vote::set_ghost_vote,
+        language::set_language,
+        question::{ask, judge},
+        rules::set_vote_rules,
         set_lives::set_lives,
-        vote::{end_voting, start_voting, vote},
+        standings::standings,
+        stats::{leaderboard, stats},
+        tie_break::{decide_tie, set_tie_break},
+        vote::{
+            end_voting,
+            extend_voting,
+            history,
+            resume_voting,
+            set_vote_changes,
+            show_votes,
+            start_voting,
+            stop_voting_timer,
+            unvote,
+            vote,
+        },
+        GameChannel,
     },
+    config::{Config, DEFAULT_CONFIG_PATH},
     data::Data,
+    embed_color,
     error::handle_error,
+    i18n::{self, tr},
+    overlay,
+    persistence::{PersistenceError, SnapshotStore, DEFAULT_SNAPSHOT_PATH},
+    questions::{QuestionBank, DEFAULT_QUESTIONS_DIR},
+    set_embed_color,
+    t,
+    twitch::{self, TwitchConfig},
     Error,
 };
 use poise::{
-    serenity_prelude::{ClientBuilder, GatewayIntents, UserId},
+    serenity_prelude::{
+        self,
+        ClientBuilder,
+        CreateEmbed,
+        CreateMessage,
+        GatewayIntents,
+        Mentionable,
+    },
     FrameworkError,
 };
+use tokio::net::TcpListener;
 
 #[tokio::main]
 #[allow(clippy::needless_return)] // idk why clippy warns here
 async fn main() -> Result<(), Error> {
     dotenv::dotenv().unwrap();
+
+    let config_path =
+        std::env::var("CONFIG_PATH").unwrap_or_else(|_| DEFAULT_CONFIG_PATH.to_owned());
+    let config = Config::load(config_path.as_ref())?;
+
     tracing_subscriber::fmt()
         .pretty()
-        .with_max_level(tracing::Level::INFO)
+        .with_max_level(config.bot.log_level.0)
         .init();
+    set_embed_color(config.bot.color.0);
 
     let token = std::env::var("TOKEN").expect("missing TOKEN");
+    let twitch_config = TwitchConfig::from_env()?;
+    let api_config = ApiConfig::from_env()?;
+    // the overlay server only runs if an address is given
+    let overlay_address = std::env::var("OVERLAY_ADDRESS")
+        .ok()
+        .map(|address| address.parse::<SocketAddr>())
+        .transpose()?;
     let intents = GatewayIntents::privileged().difference(GatewayIntents::MESSAGE_CONTENT);
 
-    let data = Arc::new(Data::default());
+    let snapshot_path =
+        std::env::var("SNAPSHOT_PATH").unwrap_or_else(|_| DEFAULT_SNAPSHOT_PATH.to_owned());
+    let store = SnapshotStore::new(snapshot_path);
+
+    let questions_dir =
+        std::env::var("QUESTIONS_DIR").unwrap_or_else(|_| DEFAULT_QUESTIONS_DIR.to_owned());
+    let questions = QuestionBank::load_dir(questions_dir.as_ref())?;
+    tracing::info!(count = questions.len(), dir = %questions_dir, "loaded questions");
+
+    let data = Arc::new(match store.load() {
+        Ok(Some(snapshot)) => {
+            tracing::info!(path = %store.path().display(), "restoring sessions from snapshot");
+            Data::from_snapshot(store, questions, config, snapshot)
+        },
+        Ok(None) => Data::new(store, questions, config),
+        // a broken snapshot shouldn't keep the bot from starting, but it's kept for a look at it
+        Err(PersistenceError::Json(error)) => {
+            let moved_to = store.set_aside()?;
+            tracing::error!(
+                error = %error,
+                moved_to = %moved_to.display(),
+                "the snapshot is corrupt, starting without it"
+            );
+            Data::new(store, questions, config)
+        },
+        Err(error) => return Err(error.into()),
+    });
+
+    if let Some(overlay_address) = overlay_address {
+        let listener = TcpListener::bind(overlay_address).await?;
+        tokio::spawn(overlay::serve(listener, data.overlay.subscribe()));
+    }
+
+    // bound right away, so a wrong address fails before the bot logs in
+    let api_listener = match &api_config {
+        Some(api_config) => Some(TcpListener::bind(api_config.address).await?),
+        None => None,
+    };
 
     let data_clone = data.clone();
 
+    let mut commands = vec![
+        show_game(),
+        start_game(),
+        add_user(),
+        remove_user(),
+        start_voting(),
+        vote(),
+        end_voting(),
+        extend_voting(),
+        stop_voting_timer(),
+        set_lives(),
+        set_tie_break(),
+        decide_tie(),
+        ask(),
+        judge(),
+        export_game(),
+        history(),
+        show_votes(),
+        unvote(),
+        set_vote_changes(),
+        set_audience_vote(),
+        audience_vote(),
+        set_ghost_vote(),
+        set_vote_rules(),
+        set_finale(),
+        standings(),
+        stats(),
+        leaderboard(),
+        set_language(),
+        config_command(),
+    ];
+    // the German texts are the default, English clients see the English ones
+    i18n::localize_commands(&mut commands);
+
     let framework = poise::Framework::builder()
         .options(poise::FrameworkOptions {
-            commands: vec![
-                show_game(),
-                start_game(),
-                add_user(),
-                remove_user(),
-                start_voting(),
-                vote(),
-                end_voting(),
-                set_lives(),
-            ],
+            commands,
             on_error: |err: FrameworkError<'_, Arc<Data>, Error>| Box::pin(handle_error(err)),
-            owners: HashSet::from_iter([UserId::new(350749990681051149)]),
+            post_command: |ctx| Box::pin(async move { ctx.data().persist_or_log().await }),
+            owners: HashSet::from_iter(data.config.bot.owners.iter().copied()),
             ..Default::default()
         })
         .setup(|ctx, _ready, framework| {
             Box::pin(async move {
                 poise::builtins::register_globally(ctx, &framework.options().commands).await?;
 
+                announce_restored_sessions(ctx, &data_clone).await;
+
+                data_clone.overlay.attach(ctx.clone());
+                data_clone.publish_overlay().await;
+
+                if let Some(twitch_config) = twitch_config {
+                    tokio::spawn(twitch::run(ctx.clone(), data_clone.clone(), twitch_config));
+                }
+
+                if let (Some(listener), Some(api_config)) = (api_listener, api_config) {
+                    let api = ControlApi::new(ctx.clone(), data_clone.clone(), api_config.token);
+                    tokio::spawn(api::serve(listener, Arc::new(api)));
+                }
+
                 Ok(data_clone)
             })
         })
@@ -65,3 +196,62 @@ async fn main() -> Result<(), Error> {
 
     Ok(())
 }
+
+/// Lets the players of every restored game know that their game survived a restart of the bot,
+/// and picks up their votings again
+async fn announce_restored_sessions(ctx: &serenity_prelude::Context, data: &Arc<Data>) {
+    for (guild_id, session) in data.sessions().await {
+        let locale = session.locale().await;
+        let (channel_id, description, has_voting) = {
+            let lock = session.engine.lock().await;
+            let Some(engine) = lock.as_ref() else {
+                continue;
+            };
+            let game = engine.game();
+
+            let mut description = t!(locale, "restore.game", moderator = game.moderator.mention());
+            if engine.voting().is_some() {
+                description.push('\n');
+                description.push_str(tr(locale, "restore.voting"));
+            }
+
+            (game.channel_id, description, engine.voting().is_some())
+        };
+
+        // one deleted channel shouldn't keep the other games from being restored
+        if let Err(error) = channel_id
+            .send_message(
+                ctx,
+                CreateMessage::new().embed(
+                    CreateEmbed::default()
+                        .title(t!(locale, "restore.title"))
+                        .description(description)
+                        .color(embed_color()),
+                ),
+            )
+            .await
+        {
+            tracing::error!(%guild_id, error = %error, "failed to announce the restored game");
+        }
+
+        if has_voting {
+            let serenity = ctx.clone();
+            let data = data.clone();
+
+            tokio::spawn(async move {
+                let channel = GameChannel {
+                    serenity: &serenity,
+                    data: &data,
+                    guild_id,
+                    channel_id,
+                    // there is only one voting per guild, so the ID of the guild keeps it apart
+                    id: guild_id.get(),
+                };
+
+                if let Err(error) = resume_voting(channel).await {
+                    tracing::error!(%guild_id, error = %error, "failed to resume the restored voting");
+                }
+            });
+        }
+    }
+}
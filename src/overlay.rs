@@ -0,0 +1,269 @@
+use std::{collections::HashMap, sync::Arc};
+
+use chrono::{DateTime, Utc};
+use poise::serenity_prelude::{self, futures::SinkExt, GuildId, UserId};
+use serde::Serialize;
+use tokio::{
+    io::{AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+    sync::{watch, Mutex, OnceCell},
+};
+use tokio_tungstenite::{
+    tungstenite::{handshake::derive_accept_key, protocol::Role, Message},
+    WebSocketStream,
+};
+
+use crate::{
+    engine::{sum_up_votes, GameEngine, MemberVoteCount, Phase},
+    http::{self, Request},
+    i18n::{Locale, Localize},
+    t,
+};
+
+/// The page the streamer adds as a browser source
+const OVERLAY_PAGE: &str = include_str!("../overlay/index.html");
+
+/// What the overlay shows of a game
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OverlayState {
+    pub phase: Phase,
+    pub round: u32,
+    pub players: Vec<OverlayPlayer>,
+    pub voting: Option<OverlayVoting>,
+    pub labels: OverlayLabels,
+}
+
+/// The texts of the page in the language of the guild
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OverlayLabels {
+    pub phase: String,
+    pub round: String,
+    pub anonymous: String,
+    pub audience: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OverlayPlayer {
+    pub id: UserId,
+    // the display name on the server, the ID until it's known
+    pub name: String,
+    pub lives: i32,
+    pub eliminated: bool,
+    // whether the player voted in the running voting
+    pub voted: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OverlayVoting {
+    pub deadline: Option<DateTime<Utc>>,
+    pub candidates: Option<Vec<UserId>>,
+    // `None` if the voting is anonymous
+    pub counts: Option<MemberVoteCount>,
+    pub audience_votes: usize,
+    pub twitch_votes: usize,
+}
+
+impl OverlayState {
+    pub fn new(engine: &GameEngine, locale: Locale) -> Self {
+        let game = engine.game();
+        let voting = engine.voting();
+
+        let mut players = game
+            .members
+            .iter()
+            .map(|(player, lives)| OverlayPlayer {
+                id: *player,
+                name: player.to_string(),
+                lives: *lives,
+                eliminated: *lives <= 0,
+                voted: voting.is_some_and(|voting| voting.map.contains_key(player)),
+            })
+            .collect::<Vec<_>>();
+        players.sort_by_key(|player| player.id);
+
+        Self {
+            phase: engine.phase(),
+            round: game.round,
+            players,
+            voting: voting.map(|voting| OverlayVoting {
+                deadline: voting.deadline,
+                candidates: voting.candidates.clone(),
+                counts: (!voting.anonymous).then(|| sum_up_votes(&voting.map, game)),
+                audience_votes: voting.audience.len(),
+                twitch_votes: voting.twitch.len(),
+            }),
+            labels: OverlayLabels {
+                phase: engine.phase().localize(locale),
+                round: t!(locale, "question.round", round = game.round),
+                anonymous: t!(locale, "overlay.anonymous"),
+                audience: t!(locale, "overlay.audience"),
+            },
+        }
+    }
+}
+
+/// Every guild with a game, as sent to the overlay
+pub type OverlayStates = HashMap<GuildId, OverlayState>;
+
+/// Hands the latest [OverlayStates] to every connected overlay
+pub struct Overlay {
+    sender: watch::Sender<Arc<OverlayStates>>,
+    // used to look up the names of the players
+    discord: OnceCell<serenity_prelude::Context>,
+    names: Mutex<HashMap<(GuildId, UserId), String>>,
+}
+
+impl Default for Overlay {
+    fn default() -> Self {
+        Self {
+            sender: watch::Sender::new(Arc::default()),
+            discord: OnceCell::new(),
+            names: Mutex::default(),
+        }
+    }
+}
+
+impl Overlay {
+    /// Names can only be shown once this is set
+    pub fn attach(&self, ctx: serenity_prelude::Context) {
+        // only the first context is needed, it stays valid across reconnects
+        let _ = self.discord.set(ctx);
+    }
+
+    pub fn subscribe(&self) -> watch::Receiver<Arc<OverlayStates>> {
+        self.sender.subscribe()
+    }
+
+    /// Fills in the names of the players and pushes the states to every overlay
+    pub async fn publish(&self, mut states: OverlayStates) {
+        for (guild_id, state) in &mut states {
+            self.fill_names(*guild_id, state).await;
+        }
+
+        self.sender.send_if_modified(|current| {
+            if **current == states {
+                return false;
+            }
+            *current = Arc::new(states);
+            true
+        });
+    }
+
+    pub async fn fill_names(&self, guild_id: GuildId, state: &mut OverlayState) {
+        for player in &mut state.players {
+            if let Some(name) = self.name(guild_id, player.id).await {
+                player.name = name;
+            }
+        }
+    }
+
+    async fn name(&self, guild_id: GuildId, player: UserId) -> Option<String> {
+        if let Some(name) = self.names.lock().await.get(&(guild_id, player)) {
+            return Some(name.clone());
+        }
+
+        let ctx = self.discord.get()?;
+        let name = guild_id
+            .member(ctx, player)
+            .await
+            .ok()?
+            .display_name()
+            .to_owned();
+        self.names
+            .lock()
+            .await
+            .insert((guild_id, player), name.clone());
+
+        Some(name)
+    }
+}
+
+/// Serves the overlay page on `/` and the state of the game as a WebSocket feed on `/ws`.
+///
+/// Both take `?guild=<ID>`, otherwise the first game found is shown.
+pub async fn serve(listener: TcpListener, overlay: watch::Receiver<Arc<OverlayStates>>) {
+    if let Ok(address) = listener.local_addr() {
+        tracing::info!(%address, "serving the overlay");
+    }
+
+    loop {
+        let stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(error) => {
+                tracing::warn!(error = %error, "failed to accept an overlay connection");
+                continue;
+            },
+        };
+
+        let overlay = overlay.clone();
+        tokio::spawn(async move {
+            if let Err(error) = handle_connection(stream, overlay).await {
+                tracing::debug!(error = %error, "overlay connection failed");
+            }
+        });
+    }
+}
+
+async fn handle_connection(
+    stream: TcpStream,
+    overlay: watch::Receiver<Arc<OverlayStates>>,
+) -> Result<(), crate::Error> {
+    let mut stream = BufReader::new(stream);
+    let request = Request::read(&mut stream).await?;
+
+    let guild_id = request
+        .query_param("guild")
+        .and_then(|guild_id| guild_id.parse().ok());
+
+    match (request.path.as_str(), request.header("sec-websocket-key")) {
+        ("/ws", Some(key)) => {
+            let response = format!(
+                "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: \
+                 Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+                derive_accept_key(key.as_bytes())
+            );
+            stream.write_all(response.as_bytes()).await?;
+
+            let websocket = WebSocketStream::from_raw_socket(stream, Role::Server, None).await;
+            push_states(websocket, overlay, guild_id).await
+        },
+        ("/" | "/index.html", _) => {
+            http::respond(
+                &mut stream,
+                "200 OK",
+                "text/html; charset=utf-8",
+                OVERLAY_PAGE.as_bytes(),
+            )
+            .await?;
+            Ok(())
+        },
+        _ => {
+            http::respond(&mut stream, "404 Not Found", "text/plain", b"Not Found").await?;
+            Ok(())
+        },
+    }
+}
+
+/// Sends the state of the game right away and again on every change. `null` means there's no
+/// game.
+async fn push_states(
+    mut websocket: WebSocketStream<BufReader<TcpStream>>,
+    mut overlay: watch::Receiver<Arc<OverlayStates>>,
+    guild_id: Option<GuildId>,
+) -> Result<(), crate::Error> {
+    loop {
+        let json = {
+            let states = overlay.borrow_and_update();
+            let state = match guild_id {
+                Some(guild_id) => states.get(&guild_id),
+                None => states.values().next(),
+            };
+            serde_json::to_string(&state)?
+        };
+        websocket.send(Message::Text(json)).await?;
+
+        if overlay.changed().await.is_err() {
+            return Ok(());
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use poise::serenity_prelude::GuildId;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::{config::GameConfigOverrides, engine::GameEngine, i18n::Locale, stats::StatsStore};
+
+pub const DEFAULT_SNAPSHOT_PATH: &str = "ddf_snapshot.json";
+
+#[derive(Debug, thiserror::Error)]
+pub enum PersistenceError {
+    #[error("Der Spielstand konnte nicht gelesen oder geschrieben werden: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Der Spielstand ist ungültig: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// Everything that is needed to resume the games of all guilds after a restart
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub sessions: HashMap<GuildId, SessionSnapshot>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SessionSnapshot {
+    pub engine: Option<GameEngine>,
+    #[serde(default)]
+    pub stats: StatsStore,
+    #[serde(default)]
+    pub locale: Locale,
+    #[serde(default)]
+    pub config: GameConfigOverrides,
+}
+
+/// Stores [Snapshot]s as a JSON file on disk
+#[derive(Debug)]
+pub struct SnapshotStore {
+    path: PathBuf,
+    // commands and long-running tasks persist at the same time
+    writing: Mutex<()>,
+}
+
+impl SnapshotStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            writing: Mutex::new(()),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns `None` if no snapshot has been written yet
+    pub fn load(&self) -> Result<Option<Snapshot>, PersistenceError> {
+        match std::fs::read(&self.path) {
+            Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Moves a snapshot that can't be read out of the way, so the bot can start without it.
+    /// Returns where it was moved to.
+    pub fn set_aside(&self) -> Result<PathBuf, PersistenceError> {
+        let corrupt_path = self.path.with_extension("json.corrupt");
+        std::fs::rename(&self.path, &corrupt_path)?;
+        Ok(corrupt_path)
+    }
+
+    /// Writes to a temporary file first, so a crash mid-write never leaves a corrupted snapshot.
+    /// Only one save runs at a time and the snapshot is only taken once it's this save's turn, so
+    /// an older snapshot never overwrites a newer one.
+    pub async fn save(
+        &self,
+        snapshot: impl Future<Output = Snapshot>,
+    ) -> Result<(), PersistenceError> {
+        let _writing = self.writing.lock().await;
+        let json = serde_json::to_vec_pretty(&snapshot.await)?;
+
+        let tmp_path = self.path.with_extension("json.tmp");
+        tokio::fs::write(&tmp_path, json).await?;
+        tokio::fs::rename(&tmp_path, &self.path).await?;
+
+        Ok(())
+    }
+}
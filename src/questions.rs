@@ -0,0 +1,154 @@
+use std::{
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use rand::seq::IteratorRandom;
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_QUESTIONS_DIR: &str = "questions";
+
+#[derive(Debug, thiserror::Error)]
+pub enum QuestionError {
+    #[error("Die Fragen in `{}` konnten nicht gelesen werden: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("Die Fragen in `{}` sind ungültig: {source}", path.display())]
+    Json {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+
+    #[error("Die Fragen in `{}` sind ungültig: {source}", path.display())]
+    Toml {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Question {
+    pub question: String,
+    pub answer: String,
+    pub category: String,
+    // other answers that count as correct as well
+    #[serde(default)]
+    pub alternatives: Vec<String>,
+}
+
+/// The content of a single question file
+#[derive(Debug, Deserialize)]
+pub struct QuestionSet {
+    pub questions: Vec<Question>,
+}
+
+impl QuestionSet {
+    /// Reads a `.json` or `.toml` file, the format is picked by the extension
+    pub fn from_file(path: &Path) -> Result<Self, QuestionError> {
+        let content = std::fs::read_to_string(path).map_err(|source| QuestionError::Io {
+            path: path.to_owned(),
+            source,
+        })?;
+
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("toml") => toml::from_str(&content).map_err(|source| QuestionError::Toml {
+                path: path.to_owned(),
+                source,
+            }),
+            _ => serde_json::from_str(&content).map_err(|source| QuestionError::Json {
+                path: path.to_owned(),
+                source,
+            }),
+        }
+    }
+}
+
+/// All questions of every question set
+#[derive(Debug, Default)]
+pub struct QuestionBank {
+    questions: Vec<Question>,
+}
+
+impl QuestionBank {
+    pub fn new(questions: Vec<Question>) -> Self {
+        Self { questions }
+    }
+
+    /// Loads every `.json` and `.toml` file in the directory.
+    ///
+    /// A missing directory results in an empty bank, as the bot works without questions as well.
+    pub fn load_dir(dir: &Path) -> Result<Self, QuestionError> {
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Self::default()),
+            Err(source) => {
+                return Err(QuestionError::Io {
+                    path: dir.to_owned(),
+                    source,
+                })
+            },
+        };
+
+        let mut paths = Vec::new();
+        for entry in entries {
+            let path = entry
+                .map_err(|source| QuestionError::Io {
+                    path: dir.to_owned(),
+                    source,
+                })?
+                .path();
+
+            if matches!(
+                path.extension().and_then(|extension| extension.to_str()),
+                Some("json" | "toml")
+            ) {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+
+        let mut questions = Vec::new();
+        for path in paths {
+            questions.extend(QuestionSet::from_file(&path)?.questions);
+        }
+
+        Ok(Self::new(questions))
+    }
+
+    pub fn len(&self) -> usize {
+        self.questions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.questions.is_empty()
+    }
+
+    pub fn categories(&self) -> Vec<&str> {
+        let mut categories = self
+            .questions
+            .iter()
+            .map(|question| question.category.as_str())
+            .collect::<Vec<_>>();
+        categories.sort_unstable();
+        categories.dedup();
+        categories
+    }
+
+    /// Picks a random question that isn't part of `asked`, optionally from a single category
+    pub fn draw<'a>(
+        &self,
+        asked: impl IntoIterator<Item = &'a Question>,
+        category: Option<&str>,
+    ) -> Option<&Question> {
+        let asked = asked.into_iter().collect::<Vec<_>>();
+
+        self.questions
+            .iter()
+            .filter(|question| category.is_none_or(|category| question.category == category))
+            .filter(|question| !asked.contains(question))
+            .choose(&mut rand::thread_rng())
+    }
+}
@@ -0,0 +1,122 @@
+use std::fmt;
+
+use poise::serenity_prelude::{Mentionable, UserId};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    game::Game,
+    i18n::{Locale, Localize},
+    t,
+};
+
+#[non_exhaustive]
+#[derive(Debug, thiserror::Error)]
+pub enum VoteRuleError {
+    SelfVote,
+    FewestLives(UserId),
+    SameTargetTwice(UserId),
+    Moderator,
+}
+
+impl Localize for VoteRuleError {
+    fn localize(&self, locale: Locale) -> String {
+        match self {
+            VoteRuleError::SelfVote => t!(locale, "errors.rules.self_vote"),
+            VoteRuleError::FewestLives(player) => {
+                t!(
+                    locale,
+                    "errors.rules.fewest_lives",
+                    player = player.mention()
+                )
+            },
+            VoteRuleError::SameTargetTwice(player) => {
+                t!(
+                    locale,
+                    "errors.rules.same_target_twice",
+                    player = player.mention()
+                )
+            },
+            VoteRuleError::Moderator => t!(locale, "errors.rules.moderator"),
+        }
+    }
+}
+
+impl fmt::Display for VoteRuleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.localize(Locale::default()))
+    }
+}
+
+/// The house rules on who can be voted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VoteRules {
+    pub allow_self_vote: bool,
+    pub allow_fewest_lives: bool,
+    // voting the same player as in the previous round
+    pub allow_same_target_twice: bool,
+    pub allow_moderator: bool,
+}
+
+impl Default for VoteRules {
+    fn default() -> Self {
+        Self {
+            allow_self_vote: false,
+            allow_fewest_lives: true,
+            allow_same_target_twice: true,
+            allow_moderator: false,
+        }
+    }
+}
+
+impl VoteRules {
+    /// Checks a vote of a player against the rules.
+    ///
+    /// In a runoff the candidates are fixed, so only the self-vote and moderator rules apply there.
+    pub fn check(
+        &self,
+        game: &Game,
+        voter: UserId,
+        target: UserId,
+        runoff: bool,
+    ) -> Result<(), VoteRuleError> {
+        if !self.allow_self_vote && voter == target {
+            return Err(VoteRuleError::SelfVote);
+        }
+        if !self.allow_moderator && target == game.moderator {
+            return Err(VoteRuleError::Moderator);
+        }
+        if runoff {
+            return Ok(());
+        }
+
+        if !self.allow_fewest_lives && has_fewest_lives(game, target) {
+            return Err(VoteRuleError::FewestLives(target));
+        }
+        if !self.allow_same_target_twice && previous_vote(game, voter) == Some(target) {
+            return Err(VoteRuleError::SameTargetTwice(target));
+        }
+
+        Ok(())
+    }
+}
+
+/// Nobody has the fewest lives if every living player has the same amount
+fn has_fewest_lives(game: &Game, player: UserId) -> bool {
+    let living = game.members.values().filter(|lives| **lives > 0);
+    let (Some(fewest), Some(most)) = (living.clone().min(), living.max()) else {
+        return false;
+    };
+
+    fewest != most && game.members.get(&player) == Some(fewest)
+}
+
+/// The player `voter` voted in the last voting of the previous round
+fn previous_vote(game: &Game, voter: UserId) -> Option<UserId> {
+    game.rounds
+        .iter()
+        .rev()
+        .find(|record| record.round < game.round)
+        .and_then(|record| record.votes.get(&voter))
+        .copied()
+}
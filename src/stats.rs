@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use poise::serenity_prelude::UserId;
+use serde::{Deserialize, Serialize};
+
+use crate::{engine::Event, game::Game};
+
+/// What a player did across every finished game of a guild
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlayerStats {
+    pub games_played: u32,
+    pub wins: u32,
+    // all places added up, see [PlayerStats::average_place]
+    pub place_sum: u32,
+    pub best_place: Option<u32>,
+    pub votes_received: u32,
+    pub votes_cast: u32,
+    // votings that cost the player a life
+    pub times_voted_out: u32,
+}
+
+impl PlayerStats {
+    pub fn average_place(&self) -> Option<f64> {
+        (self.games_played > 0).then(|| f64::from(self.place_sum) / f64::from(self.games_played))
+    }
+}
+
+/// The [PlayerStats] of a guild
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StatsStore {
+    pub players: HashMap<UserId, PlayerStats>,
+    // when the last recorded game finished, so a game isn't recorded twice
+    pub last_game_finished_at: Option<DateTime<Utc>>,
+}
+
+impl StatsStore {
+    pub fn get(&self, player: UserId) -> PlayerStats {
+        self.players.get(&player).copied().unwrap_or_default()
+    }
+
+    /// Adds a finished game to the stats. Returns `false` if the game isn't finished or was
+    /// already recorded.
+    pub fn record_game(&mut self, game: &Game) -> bool {
+        let Some((finished_at, winner, standings)) =
+            game.log.iter().rev().find_map(|entry| match &entry.event {
+                Event::GameFinished { winner, standings } => Some((entry.at, winner, standings)),
+                _ => None,
+            })
+        else {
+            return false;
+        };
+        if self
+            .last_game_finished_at
+            .is_some_and(|last| last >= finished_at)
+        {
+            return false;
+        }
+        self.last_game_finished_at = Some(finished_at);
+
+        for standing in standings {
+            let stats = self.players.entry(standing.player).or_default();
+
+            stats.games_played += 1;
+            stats.place_sum += standing.place;
+            stats.best_place = Some(
+                stats
+                    .best_place
+                    .map_or(standing.place, |best| best.min(standing.place)),
+            );
+            if *winner == Some(standing.player) {
+                stats.wins += 1;
+            }
+        }
+
+        for record in &game.rounds {
+            for (voter, target) in &record.votes {
+                self.players.entry(*voter).or_default().votes_cast += 1;
+                self.players.entry(*target).or_default().votes_received += 1;
+            }
+            for player in &record.lost_life {
+                self.players.entry(*player).or_default().times_voted_out += 1;
+            }
+        }
+
+        true
+    }
+
+    /// The players with the most wins first, then by their average place
+    pub fn leaderboard(&self) -> Vec<(UserId, PlayerStats)> {
+        let mut players = self
+            .players
+            .iter()
+            .filter(|(_, stats)| stats.games_played > 0)
+            .map(|(player, stats)| (*player, *stats))
+            .collect::<Vec<_>>();
+
+        // everyone on the leaderboard played at least one game
+        let average_place = |stats: &PlayerStats| stats.average_place().unwrap_or(f64::MAX);
+        players.sort_by(|(player_a, a), (player_b, b)| {
+            b.wins
+                .cmp(&a.wins)
+                .then(average_place(a).total_cmp(&average_place(b)))
+                .then(b.games_played.cmp(&a.games_played))
+                .then(player_a.cmp(player_b))
+        });
+
+        players
+    }
+}
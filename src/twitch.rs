@@ -0,0 +1,349 @@
+use std::{env, sync::Arc, time::Duration};
+
+use poise::serenity_prelude::{self, GuildId, UserId};
+use tokio::{
+    io::{
+        split,
+        AsyncBufReadExt,
+        AsyncRead,
+        AsyncWrite,
+        AsyncWriteExt,
+        BufReader,
+        Lines,
+        ReadHalf,
+        WriteHalf,
+    },
+    net::TcpStream,
+};
+use tokio_rustls::{
+    rustls::{pki_types::ServerName, ClientConfig, RootCertStore},
+    TlsConnector,
+};
+
+use crate::{data::Data, engine::Command};
+
+pub const DEFAULT_TWITCH_SERVER: &str = "irc.chat.twitch.tv";
+pub const DEFAULT_TWITCH_PORT: u16 = 6697;
+
+/// Twitch lets anyone read the chat with a `justinfan` nick and no token
+pub const ANONYMOUS_NICK: &str = "justinfan12345";
+
+/// How similar a name from the chat has to be to the name of a player, from 0 to 1
+const MIN_NAME_SIMILARITY: f64 = 0.8;
+
+const RECONNECT_DELAY: Duration = Duration::from_secs(10);
+
+#[non_exhaustive]
+#[derive(Debug, thiserror::Error)]
+pub enum TwitchError {
+    #[error("Die Verbindung zum Twitch-Chat ist fehlgeschlagen: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("`{0}` ist kein gültiger Servername.")]
+    InvalidServerName(String),
+
+    #[error("`{0}` muss gesetzt sein, um den Twitch-Chat zu verbinden.")]
+    MissingSetting(&'static str),
+
+    #[error("`{0}` hat einen ungültigen Wert: `{1}`")]
+    InvalidSetting(&'static str, String),
+}
+
+/// The connection settings, read from the same environment as `TOKEN`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TwitchConfig {
+    pub server: String,
+    pub port: u16,
+    pub tls: bool,
+    pub nick: String,
+    // without the `oauth:` prefix, `None` to read the chat anonymously
+    pub token: Option<String>,
+    // without the leading `#`
+    pub channel: String,
+    // the guild whose game gets the votes of the chat
+    pub guild_id: GuildId,
+}
+
+impl TwitchConfig {
+    /// Returns `None` if `TWITCH_CHANNEL` isn't set, the chat isn't connected then
+    pub fn from_env() -> Result<Option<Self>, TwitchError> {
+        let Ok(channel) = env::var("TWITCH_CHANNEL") else {
+            return Ok(None);
+        };
+        let guild_id = env::var("TWITCH_GUILD_ID")
+            .map_err(|_| TwitchError::MissingSetting("TWITCH_GUILD_ID"))?;
+
+        Ok(Some(Self {
+            server: env::var("TWITCH_SERVER").unwrap_or_else(|_| DEFAULT_TWITCH_SERVER.to_owned()),
+            port: parse_setting("TWITCH_PORT", DEFAULT_TWITCH_PORT)?,
+            tls: parse_setting("TWITCH_TLS", true)?,
+            nick: env::var("TWITCH_NICK").unwrap_or_else(|_| ANONYMOUS_NICK.to_owned()),
+            token: env::var("TWITCH_TOKEN")
+                .ok()
+                .map(|token| token.trim_start_matches("oauth:").to_owned()),
+            channel: channel.trim_start_matches('#').to_lowercase(),
+            guild_id: guild_id
+                .parse()
+                .map_err(|_| TwitchError::InvalidSetting("TWITCH_GUILD_ID", guild_id))?,
+        }))
+    }
+}
+
+fn parse_setting<T: std::str::FromStr>(name: &'static str, default: T) -> Result<T, TwitchError> {
+    match env::var(name) {
+        Ok(value) => value
+            .parse()
+            .map_err(|_| TwitchError::InvalidSetting(name, value)),
+        Err(_) => Ok(default),
+    }
+}
+
+/// A message of the stream chat
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChatMessage {
+    // the login name of the viewer
+    pub user: String,
+    pub text: String,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum IrcLine<'a> {
+    Ping(&'a str),
+    Privmsg { user: &'a str, text: &'a str },
+}
+
+/// Only the lines the client cares about, everything else is `None`
+fn parse_line(line: &str) -> Option<IrcLine<'_>> {
+    // tags aren't requested, but skipping them is cheap
+    let line = match line.strip_prefix('@') {
+        Some(tagged) => tagged.split_once(' ')?.1,
+        None => line,
+    };
+
+    if let Some(payload) = line.strip_prefix("PING ") {
+        return Some(IrcLine::Ping(payload));
+    }
+
+    let (prefix, rest) = line.strip_prefix(':')?.split_once(' ')?;
+    let (command, rest) = rest.split_once(' ')?;
+    if command != "PRIVMSG" {
+        return None;
+    }
+    let (_channel, text) = rest.split_once(" :")?;
+    let user = prefix.split_once('!').map_or(prefix, |(nick, _)| nick);
+
+    Some(IrcLine::Privmsg { user, text })
+}
+
+/// The name of `!vote <name>`, without a leading `@`
+pub fn parse_vote(text: &str) -> Option<&str> {
+    let (command, name) = text.trim().split_once(char::is_whitespace)?;
+    if !command.eq_ignore_ascii_case("!vote") {
+        return None;
+    }
+
+    let name = name.trim().trim_start_matches('@');
+    (!name.is_empty()).then_some(name)
+}
+
+/// Finds the player a viewer meant by `name`. Every player can go by several names (username,
+/// display name, nickname), small typos are tolerated as long as only one player fits.
+pub fn match_player(name: &str, players: &[(UserId, Vec<String>)]) -> Option<UserId> {
+    let name = name.to_lowercase();
+
+    let exact = players
+        .iter()
+        .filter(|(_, names)| {
+            names
+                .iter()
+                .any(|candidate| candidate.to_lowercase() == name)
+        })
+        .map(|(player, _)| *player)
+        .collect::<Vec<_>>();
+    if let [player] = exact[..] {
+        return Some(player);
+    }
+    if !exact.is_empty() {
+        return None;
+    }
+
+    let mut similar = players
+        .iter()
+        .map(|(player, names)| {
+            let similarity = names
+                .iter()
+                .map(|candidate| strsim::normalized_levenshtein(&candidate.to_lowercase(), &name))
+                .fold(0.0, f64::max);
+            (*player, similarity)
+        })
+        .filter(|(_, similarity)| *similarity >= MIN_NAME_SIMILARITY)
+        .collect::<Vec<_>>();
+    similar.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+
+    match similar[..] {
+        [(player, _)] => Some(player),
+        [(player, best), (_, second), ..] if best > second => Some(player),
+        _ => None,
+    }
+}
+
+/// A connection to a Twitch-compatible IRC server
+pub struct TwitchChat<S> {
+    lines: Lines<BufReader<ReadHalf<S>>>,
+    writer: WriteHalf<S>,
+}
+
+impl<S: AsyncRead + AsyncWrite> TwitchChat<S> {
+    /// Logs in on an already open connection and joins the channel
+    pub async fn login(stream: S, config: &TwitchConfig) -> Result<Self, TwitchError> {
+        let (reader, writer) = split(stream);
+        let mut chat = Self {
+            lines: BufReader::new(reader).lines(),
+            writer,
+        };
+
+        // anonymous logins work with any password
+        let token = config.token.as_deref().unwrap_or("anonymous");
+        chat.send(&format!("PASS oauth:{token}")).await?;
+        chat.send(&format!("NICK {}", config.nick)).await?;
+        chat.send(&format!("JOIN #{}", config.channel)).await?;
+
+        Ok(chat)
+    }
+
+    /// Waits for the next chat message and answers pings on the way. Returns `None` once the
+    /// server closed the connection.
+    pub async fn next_message(&mut self) -> Result<Option<ChatMessage>, TwitchError> {
+        while let Some(line) = self.lines.next_line().await? {
+            match parse_line(&line) {
+                Some(IrcLine::Ping(payload)) => self.send(&format!("PONG {payload}")).await?,
+                Some(IrcLine::Privmsg { user, text }) => {
+                    return Ok(Some(ChatMessage {
+                        user: user.to_owned(),
+                        text: text.to_owned(),
+                    }))
+                },
+                None => (),
+            }
+        }
+
+        Ok(None)
+    }
+
+    async fn send(&mut self, line: &str) -> Result<(), TwitchError> {
+        self.writer
+            .write_all(format!("{line}\r\n").as_bytes())
+            .await?;
+        self.writer.flush().await?;
+        Ok(())
+    }
+}
+
+pub trait ChatStream: AsyncRead + AsyncWrite + Send + Unpin {}
+
+impl<S: AsyncRead + AsyncWrite + Send + Unpin> ChatStream for S {}
+
+/// Opens a connection to the configured server, with TLS unless it's turned off
+pub async fn connect(
+    config: &TwitchConfig,
+) -> Result<TwitchChat<Box<dyn ChatStream>>, TwitchError> {
+    let tcp = TcpStream::connect((config.server.as_str(), config.port)).await?;
+
+    let stream: Box<dyn ChatStream> = if config.tls {
+        let mut roots = RootCertStore::empty();
+        roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+        let tls_config = ClientConfig::builder()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+
+        let server_name = ServerName::try_from(config.server.clone())
+            .map_err(|_| TwitchError::InvalidServerName(config.server.clone()))?;
+        let tls = TlsConnector::from(Arc::new(tls_config))
+            .connect(server_name, tcp)
+            .await?;
+        Box::new(tls)
+    } else {
+        Box::new(tcp)
+    };
+
+    TwitchChat::login(stream, config).await
+}
+
+/// Feeds the `!vote`s of the chat into the game of the configured guild, reconnecting whenever
+/// the connection drops
+pub async fn run(ctx: serenity_prelude::Context, data: Arc<Data>, config: TwitchConfig) {
+    loop {
+        let result = match connect(&config).await {
+            Ok(chat) => forward_votes(&ctx, &data, &config, chat).await,
+            Err(err) => Err(err),
+        };
+        match result {
+            Ok(()) => {
+                tracing::warn!(channel = %config.channel, "twitch chat closed the connection")
+            },
+            Err(error) => tracing::error!(error = %error, "twitch chat failed"),
+        }
+
+        tokio::time::sleep(RECONNECT_DELAY).await;
+    }
+}
+
+/// The votes are persisted with the next command, writing a snapshot for every chat message
+/// would be too much. The overlay gets them right away.
+async fn forward_votes<S: AsyncRead + AsyncWrite>(
+    ctx: &serenity_prelude::Context,
+    data: &Data,
+    config: &TwitchConfig,
+    mut chat: TwitchChat<S>,
+) -> Result<(), TwitchError> {
+    tracing::info!(channel = %config.channel, "connected to the twitch chat");
+
+    while let Some(message) = chat.next_message().await? {
+        let Some(name) = parse_vote(&message.text) else {
+            continue;
+        };
+
+        let session = data.session(config.guild_id).await;
+        let players = {
+            let lock = session.engine.lock().await;
+            match lock.as_ref() {
+                Some(engine) if engine.voting().is_some() => {
+                    engine.living_players().collect::<Vec<_>>()
+                },
+                _ => continue,
+            }
+        };
+
+        let mut candidates = Vec::with_capacity(players.len());
+        for player in players {
+            let Ok(member) = config.guild_id.member(ctx, player).await else {
+                continue;
+            };
+            let names = [
+                Some(member.user.name.clone()),
+                member.user.global_name.clone(),
+                member.nick.clone(),
+            ];
+            candidates.push((player, names.into_iter().flatten().collect()));
+        }
+        let Some(target) = match_player(name, &candidates) else {
+            continue;
+        };
+
+        let result = match session.engine.lock().await.as_mut() {
+            Some(engine) => engine
+                .handle(Command::CastTwitchVote {
+                    viewer: message.user,
+                    target,
+                })
+                .map(|_| ()),
+            None => continue,
+        };
+        match result {
+            Ok(()) => data.publish_overlay().await,
+            Err(error) => tracing::debug!(error = %error, "ignored a vote from the twitch chat"),
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,153 @@
+use ddf_bot::{
+    api::{
+        is_authorized,
+        parse_body,
+        ApiError,
+        AskQuestionRequest,
+        Route,
+        SetLivesRequest,
+        StartVotingRequest,
+    },
+    commands::question::draw_question,
+    engine::{EngineError, GameEngine},
+    http::Request,
+    i18n::{localize_error, Locale, Localize},
+    questions::{Question, QuestionBank},
+};
+use poise::serenity_prelude::{ChannelId, GuildId, UserId};
+use tokio::io::BufReader;
+
+const GUILD: GuildId = GuildId::new(42);
+
+async fn read_request(raw: &str) -> Request {
+    Request::read(&mut BufReader::new(raw.as_bytes()))
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn requests_are_read_with_headers_and_body() {
+    let request = read_request(
+        "POST /api/guilds/42/lives?dry=1 HTTP/1.1\r\nHost: localhost\r\nAuthorization: Bearer \
+         secret\r\nContent-Length: 27\r\n\r\n{\"player\":\"10\",\"lives\":2}\r\n",
+    )
+    .await;
+
+    assert_eq!(request.method, "POST");
+    assert_eq!(request.path, "/api/guilds/42/lives");
+    assert_eq!(request.query_param("dry"), Some("1"));
+    assert_eq!(request.header("AUTHORIZATION"), Some("Bearer secret"));
+    assert_eq!(request.body, b"{\"player\":\"10\",\"lives\":2}\r\n");
+
+    let body: SetLivesRequest = parse_body(&request.body).unwrap();
+    assert_eq!(body.player, UserId::new(10));
+    assert_eq!(body.lives, Some(2));
+    assert_eq!(body.change, None);
+}
+
+#[tokio::test]
+async fn only_the_configured_token_is_authorized() {
+    let request =
+        read_request("GET /api/guilds/42/game HTTP/1.1\r\nAuthorization: Bearer secret\r\n\r\n")
+            .await;
+    assert!(is_authorized(&request, "secret"));
+    assert!(!is_authorized(&request, "other"));
+    assert!(!is_authorized(&request, "secreT"));
+    assert!(!is_authorized(&request, "secrets"));
+
+    let request = read_request("GET /api/guilds/42/game HTTP/1.1\r\n\r\n").await;
+    assert!(!is_authorized(&request, "secret"));
+
+    let request =
+        read_request("GET /api/guilds/42/game HTTP/1.1\r\nAuthorization: secret\r\n\r\n").await;
+    assert!(!is_authorized(&request, "secret"));
+}
+
+#[test]
+fn routes_are_matched_by_method_and_path() {
+    let routes = [
+        ("GET", "/api/guilds/42/game", Some(Route::ShowGame)),
+        (
+            "POST",
+            "/api/guilds/42/voting/start",
+            Some(Route::StartVoting),
+        ),
+        ("POST", "/api/guilds/42/voting/end/", Some(Route::EndVoting)),
+        ("POST", "/api/guilds/42/lives", Some(Route::SetLives)),
+        ("POST", "/api/guilds/42/question", Some(Route::AskQuestion)),
+        ("POST", "/api/guilds/42/game", None),
+        ("GET", "/api/guilds/42/voting/start", None),
+        ("GET", "/api/guilds/abc/game", None),
+        ("GET", "/api/game", None),
+    ];
+
+    for (method, path, route) in routes {
+        assert_eq!(
+            Route::parse(method, path),
+            route.map(|route| (GUILD, route)),
+            "{method} {path}"
+        );
+    }
+}
+
+#[test]
+fn empty_bodies_use_the_defaults() {
+    let voting: StartVotingRequest = parse_body(b"").unwrap();
+    assert_eq!(voting.duration, None);
+    assert!(!voting.anonymous);
+    assert!(!voting.override_running);
+
+    let voting: StartVotingRequest =
+        parse_body(br#"{"duration": 60, "anonymous": true, "override": true}"#).unwrap();
+    assert_eq!(voting.duration, Some(60));
+    assert!(voting.anonymous);
+    assert!(voting.override_running);
+
+    let question: AskQuestionRequest = parse_body(b" \r\n").unwrap();
+    assert_eq!(question.player, None);
+    assert_eq!(question.category, None);
+
+    // the player is required to change lives
+    assert!(matches!(
+        parse_body::<SetLivesRequest>(b""),
+        Err(ApiError::BadRequest(_))
+    ));
+}
+
+#[test]
+fn errors_are_answered_in_the_language_of_the_guild() {
+    assert_eq!(
+        ApiError::Unauthorized.localize(Locale::English),
+        "The token is missing or wrong."
+    );
+    assert_eq!(
+        ApiError::NoActiveGame.localize(Locale::German),
+        "Es gibt kein aktives Spiel."
+    );
+    assert_eq!(
+        ApiError::from(EngineError::NoActiveVoting).localize(Locale::English),
+        EngineError::NoActiveVoting.localize(Locale::English)
+    );
+
+    // asking for a question goes through `draw_question`, which can fail in the engine as well
+    let mut engine = GameEngine::new(UserId::new(1), UserId::new(1), ChannelId::new(100));
+    let questions = QuestionBank::new(vec![Question {
+        question: "Wie viel ist 6 * 7?".to_owned(),
+        answer: "42".to_owned(),
+        category: "Allgemein".to_owned(),
+        alternatives: Vec::new(),
+    }]);
+    let error = draw_question(
+        &mut engine,
+        &questions,
+        UserId::new(10),
+        None,
+        Locale::English,
+    )
+    .map(|_| ())
+    .unwrap_err();
+    assert_eq!(
+        localize_error(error.as_ref(), Locale::English),
+        "<@10> is not in the game."
+    );
+}
@@ -0,0 +1,126 @@
+use std::collections::HashMap;
+
+use ddf_bot::{
+    config::{Config, ConfigError, EmbedColor, GameConfig, GameConfigOverrides, LogLevel},
+    DEFAULT_COLOR,
+};
+use poise::serenity_prelude::UserId;
+
+#[test]
+fn a_partial_file_keeps_the_other_defaults() {
+    let config = Config::from_toml(
+        r##"
+        [bot]
+        color = "#FF0000"
+        log_level = "debug"
+
+        [game]
+        lives = 5
+        "##,
+    )
+    .unwrap();
+
+    assert_eq!(config.bot.color, EmbedColor(0xFF0000));
+    assert_eq!(config.bot.log_level, LogLevel(tracing::Level::DEBUG));
+    assert_eq!(config.bot.owners, Config::default().bot.owners);
+    assert_eq!(
+        config.game,
+        GameConfig {
+            lives: 5,
+            ..GameConfig::default()
+        }
+    );
+
+    assert_eq!(Config::from_toml("").unwrap(), Config::default());
+    assert_eq!(Config::default().bot.color, EmbedColor(DEFAULT_COLOR));
+}
+
+#[test]
+fn typos_and_invalid_values_are_rejected() {
+    let err = Config::from_toml("[game]\nlive = 5").unwrap_err();
+    assert!(err.to_string().contains("live"), "{err}");
+
+    let err = Config::from_toml("[bot]\ncolor = \"blue\"").unwrap_err();
+    assert!(err.to_string().contains("bot.color"), "{err}");
+
+    let err = Config::from_toml("[bot]\nlog_level = \"loud\"").unwrap_err();
+    assert!(err.to_string().contains("bot.log_level"), "{err}");
+}
+
+#[test]
+fn env_vars_win_over_the_file() {
+    let env = HashMap::from([
+        ("OWNERS", "1, 2"),
+        ("EMBED_COLOR", "00FF00"),
+        ("DEFAULT_LIVES", "7"),
+        ("LOBBY_TIMEOUT", "120"),
+        ("ANSWER_TIMEOUT", ""),
+    ]);
+    let mut config = Config::from_toml("[game]\nlives = 5\nanswer_timeout = 30").unwrap();
+    config
+        .apply_overrides(|name| env.get(name).map(|value| value.to_string()))
+        .unwrap();
+
+    assert_eq!(config.bot.owners, [UserId::new(1), UserId::new(2)]);
+    assert_eq!(config.bot.color, EmbedColor(0x00FF00));
+    assert_eq!(config.game.lives, 7);
+    assert_eq!(config.game.lobby_timeout, 120);
+    // empty variables are ignored
+    assert_eq!(config.game.answer_timeout, 30);
+
+    let err = config
+        .apply_overrides(|name| (name == "CONFIRM_TIMEOUT").then(|| "soon".to_owned()))
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        ConfigError::InvalidSetting("CONFIRM_TIMEOUT", _)
+    ));
+}
+
+#[test]
+fn values_out_of_range_are_rejected() {
+    assert!(GameConfig::default().validate().is_ok());
+
+    let config = GameConfig {
+        lives: 0,
+        ..GameConfig::default()
+    };
+    assert!(matches!(
+        config.validate(),
+        Err(ConfigError::OutOfRange {
+            setting: "game.lives",
+            value: 0,
+            ..
+        })
+    ));
+
+    let config = GameConfig {
+        confirm_timeout: 5,
+        ..GameConfig::default()
+    };
+    let err = config.validate().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Die Einstellung `game.confirm_timeout` muss zwischen 10 und 86400 liegen, ist aber 5."
+    );
+}
+
+#[test]
+fn guild_overrides_only_replace_what_they_set() {
+    let defaults = GameConfig::default();
+    assert_eq!(GameConfigOverrides::default().apply(defaults), defaults);
+
+    let overrides = GameConfigOverrides {
+        lives: Some(1),
+        tie_decision_timeout: Some(30),
+        ..GameConfigOverrides::default()
+    };
+    assert_eq!(
+        overrides.apply(defaults),
+        GameConfig {
+            lives: 1,
+            tie_decision_timeout: 30,
+            ..defaults
+        }
+    );
+}
@@ -0,0 +1,1873 @@
+use chrono::{TimeDelta, Utc};
+use ddf_bot::{
+    commands::{export::redact_anonymous_votes, vote::get_resolution_embeds},
+    engine::{
+        weigh_audience_votes,
+        Command,
+        EngineError,
+        Event,
+        GameEngine,
+        MemberVoteCount,
+        Phase,
+        VoteOutcome,
+    },
+    game::{
+        Answer,
+        AnswerStats,
+        AskedQuestion,
+        AudienceVote,
+        Finale,
+        FinaleRules,
+        GhostVote,
+        PlayerError,
+        Standing,
+        TieBreak,
+        VoteChange,
+        VoteChangePolicy,
+        VoteChanges,
+    },
+    i18n::Locale,
+    questions::{Question, QuestionBank},
+    rules::{VoteRuleError, VoteRules},
+    stats::{PlayerStats, StatsStore},
+};
+use poise::serenity_prelude::{ChannelId, UserId};
+
+const MODERATOR: UserId = UserId::new(1);
+const ALICE: UserId = UserId::new(10);
+const BOB: UserId = UserId::new(11);
+const CAROL: UserId = UserId::new(12);
+const DAVE: UserId = UserId::new(13);
+
+fn engine_with_players(players: &[(UserId, i32)]) -> GameEngine {
+    let mut engine = GameEngine::new(MODERATOR, MODERATOR, ChannelId::new(100));
+
+    for (player, lives) in players {
+        engine
+            .handle(Command::Join {
+                player: *player,
+                lives: *lives,
+            })
+            .unwrap();
+    }
+
+    engine
+}
+
+fn vote(engine: &mut GameEngine, voter: UserId, target: UserId) {
+    engine.handle(Command::CastVote { voter, target }).unwrap();
+}
+
+fn start_voting(engine: &mut GameEngine) {
+    engine
+        .handle(Command::StartVoting {
+            creator: MODERATOR,
+            deadline: None,
+            anonymous: false,
+        })
+        .unwrap();
+}
+
+fn question(text: &str) -> Question {
+    Question {
+        question: text.to_owned(),
+        answer: "42".to_owned(),
+        category: "Allgemein".to_owned(),
+        alternatives: Vec::new(),
+    }
+}
+
+fn outcome(events: &[Event]) -> &VoteOutcome {
+    events
+        .iter()
+        .find_map(|event| match event {
+            Event::VotingResolved { outcome, .. } => Some(outcome),
+            _ => None,
+        })
+        .expect("Expected the voting to be resolved")
+}
+
+#[test]
+fn join_adds_player_with_lives() {
+    let mut engine = engine_with_players(&[]);
+
+    let events = engine
+        .handle(Command::Join {
+            player: ALICE,
+            lives: 3,
+        })
+        .unwrap();
+
+    assert_eq!(
+        events,
+        vec![Event::PlayerJoined {
+            player: ALICE,
+            lives: 3
+        }]
+    );
+    assert_eq!(engine.game().members.get(&ALICE), Some(&3));
+    assert_eq!(engine.phase(), Phase::Lobby);
+}
+
+#[test]
+fn joining_twice_is_rejected() {
+    let mut engine = engine_with_players(&[(ALICE, 3)]);
+
+    let result = engine.handle(Command::Join {
+        player: ALICE,
+        lives: 3,
+    });
+
+    assert!(matches!(
+        result,
+        Err(EngineError::Player(PlayerError::PlayerAlreadyAdded(ALICE)))
+    ));
+}
+
+#[test]
+fn voting_needs_two_living_players() {
+    let mut engine = engine_with_players(&[(ALICE, 3), (BOB, 0)]);
+
+    let result = engine.handle(Command::StartVoting {
+        creator: MODERATOR,
+        deadline: None,
+        anonymous: false,
+    });
+
+    assert!(matches!(result, Err(EngineError::NotEnoughPlayers)));
+    assert_eq!(engine.phase(), Phase::Lobby);
+}
+
+#[test]
+fn question_round_leads_into_voting() {
+    let mut engine = engine_with_players(&[(ALICE, 3), (BOB, 3)]);
+
+    engine.handle(Command::StartQuestionRound).unwrap();
+    assert_eq!(engine.phase(), Phase::QuestionRound);
+
+    start_voting(&mut engine);
+    assert_eq!(engine.phase(), Phase::Voting);
+    assert!(matches!(
+        engine.handle(Command::StartQuestionRound),
+        Err(EngineError::VotingAlreadyActive)
+    ));
+}
+
+#[test]
+fn vote_is_recorded_once() {
+    let mut engine = engine_with_players(&[(ALICE, 3), (BOB, 3)]);
+    start_voting(&mut engine);
+
+    vote(&mut engine, ALICE, BOB);
+    let second_vote = engine.handle(Command::CastVote {
+        voter: ALICE,
+        target: ALICE,
+    });
+
+    assert!(matches!(second_vote, Err(EngineError::AlreadyVoted(ALICE))));
+    assert_eq!(engine.voting().unwrap().map.get(&ALICE), Some(&BOB));
+}
+
+#[test]
+fn vote_without_active_voting_is_rejected() {
+    let mut engine = engine_with_players(&[(ALICE, 3), (BOB, 3)]);
+
+    let result = engine.handle(Command::CastVote {
+        voter: ALICE,
+        target: BOB,
+    });
+
+    assert!(matches!(result, Err(EngineError::NoActiveVoting)));
+}
+
+#[test]
+fn vote_for_eliminated_player_is_rejected() {
+    let mut engine = engine_with_players(&[(ALICE, 3), (BOB, 3), (CAROL, 0)]);
+    start_voting(&mut engine);
+
+    let result = engine.handle(Command::CastVote {
+        voter: ALICE,
+        target: CAROL,
+    });
+
+    assert!(matches!(
+        result,
+        Err(EngineError::Player(PlayerError::PlayerEliminated(CAROL)))
+    ));
+}
+
+#[test]
+fn eliminated_player_cannot_vote() {
+    let mut engine = engine_with_players(&[(ALICE, 3), (BOB, 3), (CAROL, 0)]);
+    start_voting(&mut engine);
+
+    let result = engine.handle(Command::CastVote {
+        voter: CAROL,
+        target: ALICE,
+    });
+
+    assert!(matches!(
+        result,
+        Err(EngineError::Player(PlayerError::PlayerEliminated(CAROL)))
+    ));
+}
+
+#[test]
+fn clear_winner_loses_a_life() {
+    let mut engine = engine_with_players(&[(ALICE, 3), (BOB, 3), (CAROL, 3)]);
+    start_voting(&mut engine);
+    vote(&mut engine, ALICE, BOB);
+    vote(&mut engine, CAROL, BOB);
+    vote(&mut engine, BOB, ALICE);
+
+    let events = engine.handle(Command::EndVoting).unwrap();
+
+    assert_eq!(
+        outcome(&events),
+        &VoteOutcome::ClearWinner {
+            user: BOB,
+            num_votes: 2
+        }
+    );
+    assert!(events.contains(&Event::LifeLost {
+        player: BOB,
+        lives_left: 2
+    }));
+    assert_eq!(engine.game().members.get(&BOB), Some(&2));
+    assert_eq!(engine.phase(), Phase::Resolution);
+    assert!(engine.voting().is_none());
+}
+
+#[test]
+fn tie_costs_nobody_a_life() {
+    let mut engine = engine_with_players(&[(ALICE, 3), (BOB, 3), (CAROL, 3)]);
+    start_voting(&mut engine);
+    vote(&mut engine, ALICE, BOB);
+    vote(&mut engine, BOB, ALICE);
+
+    let events = engine.handle(Command::EndVoting).unwrap();
+
+    assert_eq!(
+        outcome(&events),
+        &VoteOutcome::NoClearWinner {
+            members_with_equal_votes: vec![ALICE, BOB],
+            max_vote_count: 1
+        }
+    );
+    assert!(!events
+        .iter()
+        .any(|event| matches!(event, Event::LifeLost { .. })));
+    assert_eq!(engine.game().members.get(&ALICE), Some(&3));
+    assert_eq!(engine.game().members.get(&BOB), Some(&3));
+}
+
+#[test]
+fn voting_without_votes_has_no_outcome() {
+    let mut engine = engine_with_players(&[(ALICE, 3), (BOB, 3)]);
+    start_voting(&mut engine);
+
+    let events = engine.handle(Command::EndVoting).unwrap();
+
+    assert_eq!(outcome(&events), &VoteOutcome::NoVotes);
+    assert_eq!(engine.phase(), Phase::Resolution);
+}
+
+#[test]
+fn losing_the_last_life_eliminates() {
+    let mut engine = engine_with_players(&[(ALICE, 3), (BOB, 1), (CAROL, 3), (DAVE, 3)]);
+    start_voting(&mut engine);
+    vote(&mut engine, ALICE, BOB);
+
+    let events = engine.handle(Command::EndVoting).unwrap();
+
+    assert!(events.contains(&Event::PlayerEliminated { player: BOB }));
+    assert!(engine.game().is_player_dead(BOB).unwrap());
+    assert_eq!(engine.phase(), Phase::Resolution);
+}
+
+#[test]
+fn last_player_alive_wins() {
+    let mut engine = engine_with_players(&[(ALICE, 3), (BOB, 1)]);
+    start_voting(&mut engine);
+    vote(&mut engine, ALICE, BOB);
+
+    let events = engine.handle(Command::EndVoting).unwrap();
+
+    assert_eq!(
+        events.last(),
+        Some(&Event::GameFinished {
+            winner: Some(ALICE),
+            standings: vec![standing(1, ALICE, 3, None), standing(2, BOB, 0, Some(1))],
+        })
+    );
+    assert_eq!(engine.phase(), Phase::Finished);
+    assert!(matches!(
+        engine.handle(Command::StartVoting {
+            creator: MODERATOR,
+            deadline: None,
+            anonymous: false,
+        }),
+        Err(EngineError::InvalidPhase(Phase::Finished))
+    ));
+}
+
+#[test]
+fn leaving_discards_votes_of_and_for_the_player() {
+    let mut engine = engine_with_players(&[(ALICE, 3), (BOB, 3), (CAROL, 3)]);
+    start_voting(&mut engine);
+    vote(&mut engine, ALICE, CAROL);
+    vote(&mut engine, CAROL, BOB);
+    vote(&mut engine, BOB, ALICE);
+
+    engine.handle(Command::Leave { player: CAROL }).unwrap();
+
+    let map = &engine.voting().unwrap().map;
+    assert_eq!(map.len(), 1);
+    assert_eq!(map.get(&BOB), Some(&ALICE));
+}
+
+#[test]
+fn leaving_can_end_the_game_like_an_elimination() {
+    let mut engine = engine_with_players(&[(ALICE, 3), (BOB, 3), (CAROL, 3)]);
+    disable_finale(&mut engine);
+    engine.handle(Command::Leave { player: CAROL }).unwrap();
+    engine.handle(Command::Leave { player: BOB }).unwrap();
+    // nothing has been played yet
+    assert_eq!(engine.phase(), Phase::Lobby);
+
+    let mut engine = engine_with_players(&[(ALICE, 3), (BOB, 3), (CAROL, 3)]);
+    disable_finale(&mut engine);
+    ask(&mut engine, ALICE, "Erste Frage");
+    engine.handle(Command::Leave { player: CAROL }).unwrap();
+    let events = engine.handle(Command::Leave { player: BOB }).unwrap();
+
+    assert_eq!(engine.phase(), Phase::Finished);
+    assert!(matches!(
+        events[..],
+        [
+            Event::PlayerLeft { player: BOB },
+            Event::GameFinished {
+                winner: Some(ALICE),
+                ..
+            }
+        ]
+    ));
+}
+
+#[test]
+fn leaving_can_start_the_finale() {
+    let mut engine = engine_with_players(&[(ALICE, 3), (BOB, 3), (CAROL, 3), (DAVE, 3)]);
+    engine
+        .handle(Command::SetFinaleRules {
+            finale_rules: FinaleRules {
+                enabled: true,
+                questions_per_player: 3,
+            },
+        })
+        .unwrap();
+    ask(&mut engine, ALICE, "Erste Frage");
+    engine
+        .handle(Command::SetLives {
+            player: DAVE,
+            lives: 0,
+        })
+        .unwrap();
+
+    let events = engine.handle(Command::Leave { player: CAROL }).unwrap();
+
+    assert_eq!(engine.phase(), Phase::Finale);
+    assert!(matches!(events.last(), Some(Event::FinaleStarted { .. })));
+}
+
+#[test]
+fn voting_deadline_can_be_changed_and_removed() {
+    let mut engine = engine_with_players(&[(ALICE, 3), (BOB, 3)]);
+    let deadline = Utc::now() + TimeDelta::seconds(60);
+    engine
+        .handle(Command::StartVoting {
+            creator: MODERATOR,
+            deadline: Some(deadline),
+            anonymous: false,
+        })
+        .unwrap();
+
+    let extended = deadline + TimeDelta::seconds(30);
+    engine
+        .handle(Command::SetVotingDeadline {
+            deadline: Some(extended),
+        })
+        .unwrap();
+    assert_eq!(engine.voting().unwrap().deadline, Some(extended));
+
+    engine
+        .handle(Command::SetVotingDeadline { deadline: None })
+        .unwrap();
+    assert_eq!(engine.voting().unwrap().deadline, None);
+}
+
+#[test]
+fn voting_deadline_needs_active_voting() {
+    let mut engine = engine_with_players(&[(ALICE, 3), (BOB, 3)]);
+
+    let result = engine.handle(Command::SetVotingDeadline { deadline: None });
+
+    assert!(matches!(result, Err(EngineError::NoActiveVoting)));
+}
+
+/// Alice and Bob both get two votes, Carol and Dave one each
+fn engine_with_tie(tie_break: TieBreak, lives: [i32; 4]) -> GameEngine {
+    let mut engine = engine_with_players(&[
+        (ALICE, lives[0]),
+        (BOB, lives[1]),
+        (CAROL, lives[2]),
+        (DAVE, lives[3]),
+    ]);
+    engine.handle(Command::SetTieBreak { tie_break }).unwrap();
+
+    start_voting(&mut engine);
+    vote(&mut engine, ALICE, BOB);
+    vote(&mut engine, BOB, ALICE);
+    vote(&mut engine, CAROL, ALICE);
+    vote(&mut engine, DAVE, BOB);
+
+    engine
+}
+
+fn lives(engine: &GameEngine, player: UserId) -> i32 {
+    engine.game().members[&player]
+}
+
+#[test]
+fn tie_break_all_tied_lose_a_life() {
+    let mut engine = engine_with_tie(TieBreak::AllLoseLife, [3, 3, 3, 3]);
+
+    let events = engine.handle(Command::EndVoting).unwrap();
+
+    assert!(events.contains(&Event::TieBroken {
+        tie_break: TieBreak::AllLoseLife,
+        losers: vec![ALICE, BOB],
+    }));
+    assert_eq!(lives(&engine, ALICE), 2);
+    assert_eq!(lives(&engine, BOB), 2);
+    assert_eq!(lives(&engine, CAROL), 3);
+}
+
+// Discord rejects messages with more than 10 embeds
+#[test]
+fn resolution_of_a_big_tie_fits_into_one_message() {
+    let players = (20..32).map(UserId::new).collect::<Vec<_>>();
+    let mut engine = engine_with_players(
+        &players
+            .iter()
+            .map(|player| (*player, 1))
+            .collect::<Vec<_>>(),
+    );
+    engine
+        .handle(Command::SetTieBreak {
+            tie_break: TieBreak::AllLoseLife,
+        })
+        .unwrap();
+    engine
+        .handle(Command::SetAudienceVote {
+            audience_vote: AudienceVote {
+                enabled: true,
+                votes_per_player_vote: None,
+            },
+        })
+        .unwrap();
+    start_voting(&mut engine);
+    for (voter, target) in players.iter().zip(players.iter().cycle().skip(1)) {
+        vote(&mut engine, *voter, *target);
+    }
+    engine
+        .handle(Command::CastAudienceVote {
+            voter: MODERATOR,
+            target: players[0],
+        })
+        .unwrap();
+    engine
+        .handle(Command::CastTwitchVote {
+            viewer: "zuschauer".to_owned(),
+            target: players[0],
+        })
+        .unwrap();
+
+    let events = engine.handle(Command::EndVoting).unwrap();
+    assert_eq!(engine.phase(), Phase::Finished);
+
+    assert!(get_resolution_embeds(&events, Locale::German).len() <= 10);
+}
+
+#[test]
+fn tie_break_fewest_lives_loses() {
+    let mut engine = engine_with_tie(TieBreak::FewestLives, [3, 2, 3, 3]);
+
+    engine.handle(Command::EndVoting).unwrap();
+
+    assert_eq!(lives(&engine, ALICE), 3);
+    assert_eq!(lives(&engine, BOB), 1);
+}
+
+#[test]
+fn tie_break_fewest_lives_stays_tied_with_equal_lives() {
+    let mut engine = engine_with_tie(TieBreak::FewestLives, [3, 3, 3, 3]);
+
+    let events = engine.handle(Command::EndVoting).unwrap();
+
+    assert!(events.contains(&Event::TieBroken {
+        tie_break: TieBreak::FewestLives,
+        losers: vec![],
+    }));
+    assert_eq!(lives(&engine, ALICE), 3);
+    assert_eq!(lives(&engine, BOB), 3);
+}
+
+#[test]
+fn tie_break_random_picks_one_of_the_tied() {
+    let mut engine = engine_with_tie(TieBreak::Random, [3, 3, 3, 3]);
+
+    engine.handle(Command::EndVoting).unwrap();
+
+    assert_eq!(lives(&engine, ALICE) + lives(&engine, BOB), 5);
+    assert_eq!(lives(&engine, CAROL) + lives(&engine, DAVE), 6);
+}
+
+#[test]
+fn tie_break_moderator_decides() {
+    let mut engine = engine_with_tie(TieBreak::Moderator, [3, 3, 3, 3]);
+
+    let events = engine.handle(Command::EndVoting).unwrap();
+    assert!(events.contains(&Event::TieBreakPending {
+        candidates: vec![ALICE, BOB],
+    }));
+    assert!(matches!(
+        engine.handle(Command::StartVoting {
+            creator: MODERATOR,
+            deadline: None,
+            anonymous: false,
+        }),
+        Err(EngineError::TieBreakPending)
+    ));
+    assert!(matches!(
+        engine.handle(Command::DecideTie { loser: CAROL }),
+        Err(EngineError::NotACandidate(CAROL))
+    ));
+
+    engine.handle(Command::DecideTie { loser: BOB }).unwrap();
+
+    assert_eq!(lives(&engine, BOB), 2);
+    assert!(engine.pending_tie().is_none());
+    assert!(matches!(
+        engine.handle(Command::DecideTie { loser: BOB }),
+        Err(EngineError::NoPendingTie)
+    ));
+}
+
+#[test]
+fn tie_stays_open_when_the_decision_is_rejected() {
+    let mut engine = engine_with_tie(TieBreak::Moderator, [3, 3, 3, 3]);
+    engine.handle(Command::EndVoting).unwrap();
+
+    engine
+        .handle(Command::SetLives {
+            player: ALICE,
+            lives: 0,
+        })
+        .unwrap();
+    engine.handle(Command::Leave { player: BOB }).unwrap();
+
+    assert!(engine.handle(Command::DecideTie { loser: ALICE }).is_err());
+    assert!(engine.handle(Command::DecideTie { loser: BOB }).is_err());
+    assert_eq!(engine.pending_tie(), Some(&[ALICE, BOB][..]));
+}
+
+#[test]
+fn tie_break_runoff_between_tied_players() {
+    let mut engine = engine_with_tie(TieBreak::Runoff, [3, 3, 3, 3]);
+
+    let events = engine.handle(Command::EndVoting).unwrap();
+    assert!(events.contains(&Event::RunoffStarted {
+        candidates: vec![ALICE, BOB],
+    }));
+    assert_eq!(engine.phase(), Phase::Voting);
+
+    assert!(matches!(
+        engine.handle(Command::CastVote {
+            voter: ALICE,
+            target: CAROL,
+        }),
+        Err(EngineError::NotACandidate(CAROL))
+    ));
+    vote(&mut engine, ALICE, BOB);
+    vote(&mut engine, CAROL, BOB);
+    vote(&mut engine, BOB, ALICE);
+
+    engine.handle(Command::EndVoting).unwrap();
+
+    assert_eq!(lives(&engine, BOB), 2);
+    assert_eq!(lives(&engine, ALICE), 3);
+    assert_eq!(engine.phase(), Phase::Resolution);
+}
+
+#[test]
+fn tied_runoff_is_not_broken_again() {
+    let mut engine = engine_with_tie(TieBreak::Runoff, [3, 3, 3, 3]);
+    engine.handle(Command::EndVoting).unwrap();
+
+    vote(&mut engine, ALICE, BOB);
+    vote(&mut engine, BOB, ALICE);
+    let events = engine.handle(Command::EndVoting).unwrap();
+
+    assert!(!events
+        .iter()
+        .any(|event| matches!(event, Event::RunoffStarted { .. })));
+    assert_eq!(lives(&engine, ALICE), 3);
+    assert_eq!(lives(&engine, BOB), 3);
+    assert_eq!(engine.phase(), Phase::Resolution);
+}
+
+#[test]
+fn asking_a_question_starts_the_round_and_is_recorded() {
+    let mut engine = engine_with_players(&[(ALICE, 3), (BOB, 3)]);
+
+    let events = engine
+        .handle(Command::AskQuestion {
+            player: ALICE,
+            question: question("Wie viel ist 6 * 7?"),
+        })
+        .unwrap();
+
+    assert_eq!(
+        events,
+        vec![
+            Event::QuestionRoundStarted,
+            Event::QuestionAsked {
+                round: 1,
+                player: ALICE,
+                question: question("Wie viel ist 6 * 7?"),
+            }
+        ]
+    );
+    assert_eq!(engine.phase(), Phase::QuestionRound);
+    assert_eq!(
+        engine.game().asked_questions,
+        vec![AskedQuestion {
+            round: 1,
+            player: ALICE,
+            question: question("Wie viel ist 6 * 7?"),
+            answer: None,
+        }]
+    );
+}
+
+#[test]
+fn asking_during_voting_is_rejected() {
+    let mut engine = engine_with_players(&[(ALICE, 3), (BOB, 3)]);
+    start_voting(&mut engine);
+
+    let result = engine.handle(Command::AskQuestion {
+        player: ALICE,
+        question: question("Wie viel ist 6 * 7?"),
+    });
+
+    assert!(matches!(result, Err(EngineError::VotingAlreadyActive)));
+}
+
+#[test]
+fn resolved_voting_starts_the_next_round() {
+    let mut engine = engine_with_players(&[(ALICE, 3), (BOB, 3)]);
+    start_voting(&mut engine);
+    vote(&mut engine, ALICE, BOB);
+    engine.handle(Command::EndVoting).unwrap();
+
+    let events = engine
+        .handle(Command::AskQuestion {
+            player: BOB,
+            question: question("Wie viel ist 6 * 7?"),
+        })
+        .unwrap();
+
+    assert!(events.contains(&Event::QuestionAsked {
+        round: 2,
+        player: BOB,
+        question: question("Wie viel ist 6 * 7?"),
+    }));
+}
+
+#[test]
+fn drawn_questions_are_not_asked_again() {
+    let bank = QuestionBank::new(vec![question("Erste Frage"), question("Zweite Frage")]);
+    let asked = [question("Erste Frage")];
+
+    for _ in 0..10 {
+        assert_eq!(bank.draw(&asked, None), Some(&question("Zweite Frage")));
+    }
+    assert_eq!(bank.draw(&asked, Some("Geschichte")), None);
+}
+
+fn ask(engine: &mut GameEngine, player: UserId, text: &str) {
+    engine
+        .handle(Command::AskQuestion {
+            player,
+            question: question(text),
+        })
+        .unwrap();
+}
+
+#[test]
+fn judged_answers_show_up_in_the_stats() {
+    let mut engine = engine_with_players(&[(ALICE, 3), (BOB, 3)]);
+    ask(&mut engine, ALICE, "Erste Frage");
+    ask(&mut engine, ALICE, "Zweite Frage");
+    ask(&mut engine, ALICE, "Dritte Frage");
+
+    let events = engine
+        .handle(Command::JudgeAnswer {
+            question: 0,
+            correct: true,
+        })
+        .unwrap();
+    assert_eq!(
+        events,
+        vec![Event::AnswerJudged {
+            round: 1,
+            player: ALICE,
+            answer: Answer::Correct,
+        }]
+    );
+    engine
+        .handle(Command::JudgeAnswer {
+            question: 1,
+            correct: false,
+        })
+        .unwrap();
+    start_voting(&mut engine);
+
+    assert_eq!(
+        engine.game().answer_stats(ALICE, Some(1)),
+        AnswerStats {
+            asked: 3,
+            correct: 1,
+            wrong: 1,
+            skipped: 1,
+        }
+    );
+    assert_eq!(
+        engine.game().answer_stats(ALICE, Some(2)),
+        AnswerStats::default()
+    );
+}
+
+#[test]
+fn answers_of_past_rounds_cannot_be_judged() {
+    let mut engine = engine_with_players(&[(ALICE, 3), (BOB, 3)]);
+    ask(&mut engine, ALICE, "Erste Frage");
+    start_voting(&mut engine);
+    vote(&mut engine, ALICE, BOB);
+    engine.handle(Command::EndVoting).unwrap();
+    ask(&mut engine, BOB, "Zweite Frage");
+
+    let result = engine.handle(Command::JudgeAnswer {
+        question: 0,
+        correct: true,
+    });
+
+    assert!(matches!(result, Err(EngineError::QuestionClosed(0))));
+}
+
+#[test]
+fn every_event_is_logged() {
+    let mut engine = engine_with_players(&[(ALICE, 3), (BOB, 3)]);
+    start_voting(&mut engine);
+    vote(&mut engine, ALICE, BOB);
+    let events = engine.handle(Command::EndVoting).unwrap();
+
+    let logged = engine
+        .game()
+        .log
+        .iter()
+        .map(|entry| entry.event.clone())
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        logged[..4],
+        [
+            Event::PlayerJoined {
+                player: ALICE,
+                lives: 3
+            },
+            Event::PlayerJoined {
+                player: BOB,
+                lives: 3
+            },
+            Event::VotingStarted {
+                creator: MODERATOR,
+                deadline: None,
+                anonymous: false
+            },
+            Event::VoteCast {
+                voter: ALICE,
+                target: BOB
+            },
+        ]
+    );
+    assert_eq!(logged[4..], events);
+}
+
+#[test]
+fn rejected_commands_are_not_logged() {
+    let mut engine = engine_with_players(&[(ALICE, 3)]);
+
+    assert!(engine.handle(Command::EndVoting).is_err());
+
+    assert_eq!(engine.game().log.len(), 1);
+}
+
+#[test]
+fn logged_events_are_tagged_with_their_type() {
+    let event = Event::VoteCast {
+        voter: ALICE,
+        target: BOB,
+    };
+
+    assert_eq!(
+        serde_json::to_value(&event).unwrap(),
+        serde_json::json!({ "type": "VoteCast", "voter": "10", "target": "11" })
+    );
+}
+
+#[test]
+fn game_with_log_survives_a_snapshot() {
+    let mut engine = engine_with_players(&[(ALICE, 3), (BOB, 3)]);
+    start_voting(&mut engine);
+    vote(&mut engine, ALICE, BOB);
+    engine.handle(Command::EndVoting).unwrap();
+
+    let json = serde_json::to_string(&engine).unwrap();
+    let restored: GameEngine = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(restored.game().log, engine.game().log);
+}
+
+#[test]
+fn resolved_votings_are_kept_as_rounds() {
+    let mut engine = engine_with_players(&[(ALICE, 3), (BOB, 3), (CAROL, 3)]);
+    start_voting(&mut engine);
+    vote(&mut engine, ALICE, BOB);
+    vote(&mut engine, CAROL, BOB);
+    engine.handle(Command::EndVoting).unwrap();
+    start_voting(&mut engine);
+    engine.handle(Command::EndVoting).unwrap();
+
+    let rounds = &engine.game().rounds;
+    assert_eq!(rounds.len(), 2);
+
+    assert_eq!(rounds[0].round, 1);
+    assert_eq!(rounds[0].votes.get(&CAROL), Some(&BOB));
+    assert_eq!(rounds[0].counts.get(&BOB), Some(&2));
+    assert_eq!(rounds[0].lost_life, vec![BOB]);
+
+    assert_eq!(rounds[1].round, 2);
+    assert_eq!(rounds[1].outcome, VoteOutcome::NoVotes);
+    assert!(rounds[1].lost_life.is_empty());
+}
+
+#[test]
+fn runoff_shares_the_round_of_its_voting() {
+    let mut engine = engine_with_tie(TieBreak::Runoff, [3, 3, 3, 3]);
+    engine.handle(Command::EndVoting).unwrap();
+    vote(&mut engine, CAROL, ALICE);
+    engine.handle(Command::EndVoting).unwrap();
+
+    let rounds = &engine.game().rounds;
+    assert_eq!(rounds.len(), 2);
+    assert_eq!((rounds[0].round, rounds[0].runoff), (1, false));
+    assert_eq!((rounds[1].round, rounds[1].runoff), (1, true));
+    assert_eq!(rounds[1].lost_life, vec![ALICE]);
+}
+
+#[test]
+fn moderator_decision_is_added_to_the_round() {
+    let mut engine = engine_with_tie(TieBreak::Moderator, [3, 3, 3, 3]);
+    engine.handle(Command::EndVoting).unwrap();
+    engine.handle(Command::DecideTie { loser: BOB }).unwrap();
+
+    assert_eq!(engine.game().rounds[0].lost_life, vec![BOB]);
+}
+
+#[test]
+fn moderator_elimination_belongs_to_the_round_of_the_voting() {
+    let mut engine = engine_with_tie(TieBreak::Moderator, [1, 1, 3, 3]);
+    engine.handle(Command::EndVoting).unwrap();
+    engine.handle(Command::DecideTie { loser: ALICE }).unwrap();
+
+    let game = engine.game();
+    assert_eq!(game.rounds[0].round, 1);
+    assert_eq!(game.eliminations.len(), 1);
+    assert_eq!(
+        (game.eliminations[0].player, game.eliminations[0].round),
+        (ALICE, 1)
+    );
+    assert!(game
+        .standings()
+        .iter()
+        .any(|standing| standing.player == ALICE && standing.eliminated_in == Some(1)));
+}
+
+#[test]
+fn full_lobby_rejects_new_players() {
+    let mut engine = engine_with_players(&[]);
+    engine
+        .handle(Command::SetMaxPlayers {
+            max_players: Some(1),
+        })
+        .unwrap();
+    engine
+        .handle(Command::Join {
+            player: ALICE,
+            lives: 3,
+        })
+        .unwrap();
+
+    let again = engine.handle(Command::Join {
+        player: ALICE,
+        lives: 3,
+    });
+    let other = engine.handle(Command::Join {
+        player: BOB,
+        lives: 3,
+    });
+
+    assert!(matches!(
+        again,
+        Err(EngineError::Player(PlayerError::PlayerAlreadyAdded(ALICE)))
+    ));
+    assert!(matches!(other, Err(EngineError::LobbyFull(1))));
+}
+
+#[test]
+fn closed_lobby_locks_the_roster() {
+    let mut engine = engine_with_players(&[(ALICE, 3)]);
+
+    assert_eq!(
+        engine.handle(Command::CloseLobby).unwrap(),
+        vec![Event::LobbyClosed]
+    );
+    let result = engine.handle(Command::Join {
+        player: BOB,
+        lives: 3,
+    });
+
+    assert!(matches!(result, Err(EngineError::LobbyClosed)));
+}
+
+const VIEWER: UserId = UserId::new(50);
+const OTHER_VIEWER: UserId = UserId::new(51);
+
+fn enable_audience_vote(engine: &mut GameEngine, votes_per_player_vote: Option<u32>) {
+    engine
+        .handle(Command::SetAudienceVote {
+            audience_vote: AudienceVote {
+                enabled: true,
+                votes_per_player_vote,
+            },
+        })
+        .unwrap();
+}
+
+fn audience_counts(events: &[Event]) -> &MemberVoteCount {
+    events
+        .iter()
+        .find_map(|event| match event {
+            Event::VotingResolved {
+                audience_counts, ..
+            } => Some(audience_counts),
+            _ => None,
+        })
+        .expect("Expected the voting to be resolved")
+}
+
+#[test]
+fn audience_cannot_vote_unless_enabled() {
+    let mut engine = engine_with_players(&[(ALICE, 3), (BOB, 3)]);
+    start_voting(&mut engine);
+
+    let result = engine.handle(Command::CastAudienceVote {
+        voter: VIEWER,
+        target: ALICE,
+    });
+
+    assert!(matches!(result, Err(EngineError::AudienceVoteDisabled)));
+}
+
+#[test]
+fn players_cannot_vote_as_audience() {
+    let mut engine = engine_with_players(&[(ALICE, 3), (BOB, 3)]);
+    enable_audience_vote(&mut engine, None);
+    start_voting(&mut engine);
+
+    let result = engine.handle(Command::CastAudienceVote {
+        voter: ALICE,
+        target: BOB,
+    });
+
+    assert!(matches!(result, Err(EngineError::PlayerInAudience(ALICE))));
+}
+
+#[test]
+fn audience_votes_are_tallied_separately() {
+    let mut engine = engine_with_players(&[(ALICE, 3), (BOB, 3)]);
+    enable_audience_vote(&mut engine, None);
+    start_voting(&mut engine);
+    vote(&mut engine, ALICE, BOB);
+    for viewer in [VIEWER, OTHER_VIEWER] {
+        engine
+            .handle(Command::CastAudienceVote {
+                voter: viewer,
+                target: ALICE,
+            })
+            .unwrap();
+    }
+
+    let events = engine.handle(Command::EndVoting).unwrap();
+
+    assert_eq!(audience_counts(&events).get(&ALICE), Some(&2));
+    assert_eq!(
+        outcome(&events),
+        &VoteOutcome::ClearWinner {
+            user: BOB,
+            num_votes: 1
+        }
+    );
+}
+
+#[test]
+fn weighted_audience_votes_count_towards_the_outcome() {
+    let mut engine = engine_with_players(&[(ALICE, 3), (BOB, 3)]);
+    enable_audience_vote(&mut engine, Some(2));
+    start_voting(&mut engine);
+    vote(&mut engine, ALICE, BOB);
+    for viewer in [VIEWER, OTHER_VIEWER] {
+        engine
+            .handle(Command::CastAudienceVote {
+                voter: viewer,
+                target: ALICE,
+            })
+            .unwrap();
+    }
+
+    let events = engine.handle(Command::EndVoting).unwrap();
+
+    assert_eq!(
+        outcome(&events),
+        &VoteOutcome::NoClearWinner {
+            members_with_equal_votes: vec![ALICE, BOB],
+            max_vote_count: 1
+        }
+    );
+}
+
+#[test]
+fn audience_votes_are_weighed_per_player() {
+    let counts = MemberVoteCount::from([(ALICE, 1)]);
+    let audience = MemberVoteCount::from([(ALICE, 5), (BOB, 2)]);
+
+    assert_eq!(
+        weigh_audience_votes(&counts, &audience, Some(3)),
+        MemberVoteCount::from([(ALICE, 2)])
+    );
+    assert_eq!(weigh_audience_votes(&counts, &audience, None), counts);
+}
+
+fn engine_with_ghost(ghost_vote: GhostVote, living: &[UserId]) -> GameEngine {
+    let mut players = vec![(ALICE, 0)];
+    players.extend(living.iter().map(|player| (*player, 3)));
+
+    let mut engine = engine_with_players(&players);
+    engine.handle(Command::SetGhostVote { ghost_vote }).unwrap();
+    start_voting(&mut engine);
+    engine
+}
+
+#[test]
+fn ghosts_with_full_vote_count_like_players() {
+    let mut engine = engine_with_ghost(GhostVote::Full, &[BOB, CAROL, DAVE]);
+    vote(&mut engine, ALICE, BOB);
+    vote(&mut engine, CAROL, DAVE);
+    vote(&mut engine, DAVE, BOB);
+
+    let events = engine.handle(Command::EndVoting).unwrap();
+
+    assert_eq!(
+        outcome(&events),
+        &VoteOutcome::ClearWinner {
+            user: BOB,
+            num_votes: 2
+        }
+    );
+}
+
+#[test]
+fn reduced_ghost_votes_need_a_partner() {
+    let mut engine = engine_with_players(&[(ALICE, 0), (BOB, 0), (CAROL, 3), (DAVE, 3)]);
+    engine
+        .handle(Command::SetGhostVote {
+            ghost_vote: GhostVote::Reduced,
+        })
+        .unwrap();
+    start_voting(&mut engine);
+    vote(&mut engine, ALICE, CAROL);
+    vote(&mut engine, BOB, CAROL);
+    vote(&mut engine, CAROL, DAVE);
+
+    let events = engine.handle(Command::EndVoting).unwrap();
+
+    assert_eq!(
+        outcome(&events),
+        &VoteOutcome::NoClearWinner {
+            members_with_equal_votes: vec![CAROL, DAVE],
+            max_vote_count: 1
+        }
+    );
+}
+
+#[test]
+fn ghosts_vote_only_in_the_last_voting_before_the_finale() {
+    const ERIN: UserId = UserId::new(14);
+    let mut engine = engine_with_players(&[(ALICE, 1), (BOB, 3), (CAROL, 1), (DAVE, 1), (ERIN, 3)]);
+    engine
+        .handle(Command::SetGhostVote {
+            ghost_vote: GhostVote::FinalOnly,
+        })
+        .unwrap();
+
+    start_voting(&mut engine);
+    vote(&mut engine, BOB, ALICE);
+    engine.handle(Command::EndVoting).unwrap();
+
+    // four players are left, so this isn't the last voting yet
+    start_voting(&mut engine);
+    let result = engine.handle(Command::CastVote {
+        voter: ALICE,
+        target: BOB,
+    });
+    assert!(matches!(
+        result,
+        Err(EngineError::Player(PlayerError::PlayerEliminated(ALICE)))
+    ));
+    vote(&mut engine, BOB, DAVE);
+    engine.handle(Command::EndVoting).unwrap();
+
+    // the next elimination decides the finalists
+    start_voting(&mut engine);
+    vote(&mut engine, ALICE, CAROL);
+    vote(&mut engine, DAVE, CAROL);
+    vote(&mut engine, CAROL, BOB);
+    let events = engine.handle(Command::EndVoting).unwrap();
+
+    assert_eq!(
+        outcome(&events),
+        &VoteOutcome::ClearWinner {
+            user: CAROL,
+            num_votes: 2
+        }
+    );
+    assert!(events
+        .iter()
+        .any(|event| matches!(event, Event::FinaleStarted { .. })));
+    assert_eq!(engine.phase(), Phase::Finale);
+}
+
+#[test]
+fn vote_of_a_player_eliminated_mid_voting_is_dropped() {
+    let mut engine = engine_with_players(&[(ALICE, 3), (BOB, 3), (CAROL, 3), (DAVE, 3)]);
+    engine
+        .handle(Command::SetGhostVote {
+            ghost_vote: GhostVote::Nobody,
+        })
+        .unwrap();
+    start_voting(&mut engine);
+    vote(&mut engine, ALICE, BOB);
+    vote(&mut engine, CAROL, DAVE);
+    vote(&mut engine, DAVE, BOB);
+
+    engine
+        .handle(Command::SetLives {
+            player: ALICE,
+            lives: 0,
+        })
+        .unwrap();
+    let events = engine.handle(Command::EndVoting).unwrap();
+
+    assert_eq!(
+        outcome(&events),
+        &VoteOutcome::NoClearWinner {
+            members_with_equal_votes: vec![BOB, DAVE],
+            max_vote_count: 1
+        }
+    );
+}
+
+#[test]
+fn anonymous_voting_stays_anonymous_in_the_runoff() {
+    let mut engine = engine_with_players(&[(ALICE, 3), (BOB, 3), (CAROL, 3)]);
+    engine
+        .handle(Command::SetTieBreak {
+            tie_break: TieBreak::Runoff,
+        })
+        .unwrap();
+    engine
+        .handle(Command::StartVoting {
+            creator: MODERATOR,
+            deadline: None,
+            anonymous: true,
+        })
+        .unwrap();
+    vote(&mut engine, ALICE, BOB);
+    vote(&mut engine, BOB, ALICE);
+
+    let events = engine.handle(Command::EndVoting).unwrap();
+
+    assert!(events.iter().any(|event| matches!(
+        event,
+        Event::VotingResolved {
+            anonymous: true,
+            ..
+        }
+    )));
+    assert!(engine.voting().is_some_and(|voting| voting.anonymous));
+    assert!(engine.game().rounds[0].anonymous);
+}
+
+#[test]
+fn export_leaves_out_the_votes_of_anonymous_votings() {
+    let mut engine = engine_with_players(&[(ALICE, 3), (BOB, 3), (CAROL, 3)]);
+    engine
+        .handle(Command::SetTieBreak {
+            tie_break: TieBreak::Runoff,
+        })
+        .unwrap();
+    engine
+        .handle(Command::StartVoting {
+            creator: MODERATOR,
+            deadline: None,
+            anonymous: true,
+        })
+        .unwrap();
+    vote(&mut engine, ALICE, BOB);
+    vote(&mut engine, BOB, ALICE);
+    engine.handle(Command::EndVoting).unwrap();
+    // the runoff is anonymous as well
+    vote(&mut engine, CAROL, BOB);
+    engine.handle(Command::EndVoting).unwrap();
+
+    start_voting(&mut engine);
+    vote(&mut engine, ALICE, CAROL);
+    engine.handle(Command::EndVoting).unwrap();
+
+    let log = redact_anonymous_votes(&engine.game().log);
+    let votes_cast = log
+        .iter()
+        .filter_map(|entry| match &entry.event {
+            Event::VoteCast { voter, target } => Some((*voter, *target)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(votes_cast, [(ALICE, CAROL)]);
+
+    let resolved = log
+        .iter()
+        .filter_map(|entry| match &entry.event {
+            Event::VotingResolved {
+                votes,
+                counts,
+                anonymous,
+                ..
+            } => Some((votes.len(), counts.values().sum::<i32>(), *anonymous)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(resolved, [(0, 2, true), (0, 1, true), (1, 1, false)]);
+}
+
+fn engine_with_vote_changes(policy: VoteChangePolicy) -> GameEngine {
+    let mut engine = engine_with_players(&[(ALICE, 3), (BOB, 3), (CAROL, 3)]);
+    engine
+        .handle(Command::SetVoteChanges {
+            vote_changes: VoteChanges { policy, show: true },
+        })
+        .unwrap();
+    start_voting(&mut engine);
+    engine
+}
+
+#[test]
+fn changed_vote_replaces_the_old_one() {
+    let mut engine = engine_with_vote_changes(VoteChangePolicy::Change);
+    vote(&mut engine, ALICE, BOB);
+
+    let events = engine
+        .handle(Command::CastVote {
+            voter: ALICE,
+            target: CAROL,
+        })
+        .unwrap();
+
+    assert_eq!(
+        events,
+        vec![Event::VoteChanged {
+            voter: ALICE,
+            from: BOB,
+            to: Some(CAROL)
+        }]
+    );
+    assert_eq!(engine.voting().unwrap().map.get(&ALICE), Some(&CAROL));
+}
+
+#[test]
+fn withdrawing_needs_the_policy() {
+    let mut engine = engine_with_vote_changes(VoteChangePolicy::Change);
+    vote(&mut engine, ALICE, BOB);
+
+    let result = engine.handle(Command::WithdrawVote { voter: ALICE });
+
+    assert!(matches!(result, Err(EngineError::WithdrawNotAllowed)));
+}
+
+#[test]
+fn vote_switches_show_up_in_the_result() {
+    let mut engine = engine_with_vote_changes(VoteChangePolicy::ChangeOrWithdraw);
+    vote(&mut engine, ALICE, BOB);
+    vote(&mut engine, ALICE, CAROL);
+    vote(&mut engine, BOB, CAROL);
+    engine.handle(Command::WithdrawVote { voter: BOB }).unwrap();
+
+    let events = engine.handle(Command::EndVoting).unwrap();
+
+    let changes = events
+        .iter()
+        .find_map(|event| match event {
+            Event::VotingResolved { changes, .. } => Some(changes),
+            _ => None,
+        })
+        .unwrap();
+    assert_eq!(
+        changes,
+        &vec![
+            VoteChange {
+                voter: ALICE,
+                from: BOB,
+                to: Some(CAROL)
+            },
+            VoteChange {
+                voter: BOB,
+                from: CAROL,
+                to: None
+            },
+        ]
+    );
+    assert_eq!(
+        outcome(&events),
+        &VoteOutcome::ClearWinner {
+            user: CAROL,
+            num_votes: 1
+        }
+    );
+}
+
+fn set_vote_rules(engine: &mut GameEngine, vote_rules: VoteRules) {
+    engine.handle(Command::SetVoteRules { vote_rules }).unwrap();
+}
+
+#[test]
+fn self_votes_are_rejected_by_default() {
+    let mut engine = engine_with_players(&[(ALICE, 3), (BOB, 3)]);
+    start_voting(&mut engine);
+
+    let result = engine.handle(Command::CastVote {
+        voter: ALICE,
+        target: ALICE,
+    });
+
+    assert!(matches!(
+        result,
+        Err(EngineError::Rule(VoteRuleError::SelfVote))
+    ));
+}
+
+#[test]
+fn player_with_fewest_lives_can_be_protected() {
+    let mut engine = engine_with_players(&[(ALICE, 3), (BOB, 1), (CAROL, 1)]);
+    set_vote_rules(
+        &mut engine,
+        VoteRules {
+            allow_fewest_lives: false,
+            ..Default::default()
+        },
+    );
+    start_voting(&mut engine);
+
+    let result = engine.handle(Command::CastVote {
+        voter: ALICE,
+        target: BOB,
+    });
+
+    assert!(matches!(
+        result,
+        Err(EngineError::Rule(VoteRuleError::FewestLives(BOB)))
+    ));
+    vote(&mut engine, BOB, ALICE);
+}
+
+#[test]
+fn same_target_twice_in_a_row_can_be_forbidden() {
+    let mut engine = engine_with_players(&[(ALICE, 3), (BOB, 3), (CAROL, 3)]);
+    set_vote_rules(
+        &mut engine,
+        VoteRules {
+            allow_same_target_twice: false,
+            ..Default::default()
+        },
+    );
+    start_voting(&mut engine);
+    vote(&mut engine, ALICE, BOB);
+    engine.handle(Command::EndVoting).unwrap();
+    start_voting(&mut engine);
+
+    let result = engine.handle(Command::CastVote {
+        voter: ALICE,
+        target: BOB,
+    });
+
+    assert!(matches!(
+        result,
+        Err(EngineError::Rule(VoteRuleError::SameTargetTwice(BOB)))
+    ));
+    vote(&mut engine, ALICE, CAROL);
+}
+
+/// ALICE (3 lives) and BOB (2 lives) are left in the finale after CAROL was eliminated
+fn engine_in_finale(questions_per_player: u32) -> GameEngine {
+    let mut engine = engine_with_players(&[(ALICE, 3), (BOB, 2), (CAROL, 1)]);
+    engine
+        .handle(Command::SetFinaleRules {
+            finale_rules: FinaleRules {
+                enabled: true,
+                questions_per_player,
+            },
+        })
+        .unwrap();
+    start_voting(&mut engine);
+    vote(&mut engine, ALICE, CAROL);
+    engine.handle(Command::EndVoting).unwrap();
+
+    engine
+}
+
+fn answer_in_finale(engine: &mut GameEngine, player: UserId, correct: bool) -> Vec<Event> {
+    ask(engine, player, "Finalfrage");
+    let index = engine.game().asked_questions.len() - 1;
+
+    engine
+        .handle(Command::JudgeAnswer {
+            question: index,
+            correct,
+        })
+        .unwrap()
+}
+
+#[test]
+fn elimination_down_to_two_players_starts_the_finale() {
+    let mut engine = engine_with_players(&[(ALICE, 2), (BOB, 3), (CAROL, 1)]);
+    start_voting(&mut engine);
+    vote(&mut engine, ALICE, CAROL);
+
+    let events = engine.handle(Command::EndVoting).unwrap();
+
+    assert_eq!(
+        events.last(),
+        Some(&Event::FinaleStarted {
+            players: [BOB, ALICE],
+            questions_per_player: 5,
+        })
+    );
+    assert_eq!(engine.phase(), Phase::Finale);
+    assert!(matches!(
+        engine.handle(Command::StartVoting {
+            creator: MODERATOR,
+            deadline: None,
+            anonymous: false,
+        }),
+        Err(EngineError::InvalidPhase(Phase::Finale))
+    ));
+}
+
+#[test]
+fn disabled_finale_keeps_voting() {
+    let mut engine = engine_with_players(&[(ALICE, 3), (BOB, 3), (CAROL, 1)]);
+    engine
+        .handle(Command::SetFinaleRules {
+            finale_rules: FinaleRules {
+                enabled: false,
+                ..Default::default()
+            },
+        })
+        .unwrap();
+    start_voting(&mut engine);
+    vote(&mut engine, ALICE, CAROL);
+
+    engine.handle(Command::EndVoting).unwrap();
+
+    assert_eq!(engine.phase(), Phase::Resolution);
+    assert_eq!(engine.game().finale, None);
+}
+
+#[test]
+fn finalists_take_turns() {
+    let mut engine = engine_in_finale(3);
+
+    let result = engine.handle(Command::AskQuestion {
+        player: BOB,
+        question: question("Finalfrage"),
+    });
+    assert!(matches!(result, Err(EngineError::NotOnTurn(ALICE))));
+
+    ask(&mut engine, ALICE, "Finalfrage");
+    let result = engine.handle(Command::AskQuestion {
+        player: BOB,
+        question: question("Noch eine Finalfrage"),
+    });
+    assert!(matches!(result, Err(EngineError::FinaleQuestionOpen)));
+
+    engine
+        .handle(Command::JudgeAnswer {
+            question: engine.game().asked_questions.len() - 1,
+            correct: true,
+        })
+        .unwrap();
+    assert_eq!(engine.game().finale.as_ref().unwrap().on_turn(), BOB);
+}
+
+#[test]
+fn finale_is_won_once_the_lead_cannot_be_caught_up() {
+    let mut engine = engine_in_finale(2);
+
+    answer_in_finale(&mut engine, ALICE, true);
+    answer_in_finale(&mut engine, BOB, false);
+    // BOB has one question left, which can't make up for two correct answers
+    let events = answer_in_finale(&mut engine, ALICE, true);
+
+    assert_eq!(
+        events.last(),
+        Some(&Event::GameFinished {
+            winner: Some(ALICE),
+            standings: vec![
+                standing(1, ALICE, 3, None),
+                standing(2, BOB, 2, None),
+                standing(3, CAROL, 0, Some(1)),
+            ],
+        })
+    );
+    assert_eq!(engine.phase(), Phase::Finished);
+}
+
+#[test]
+fn questions_from_before_the_finale_do_not_count_in_it() {
+    let mut engine = engine_with_players(&[(ALICE, 3), (BOB, 2), (CAROL, 1)]);
+    ask(&mut engine, CAROL, "Frage vor dem Finale");
+    engine
+        .handle(Command::SetLives {
+            player: CAROL,
+            lives: 0,
+        })
+        .unwrap();
+    assert_eq!(engine.phase(), Phase::Finale);
+
+    let result = engine.handle(Command::JudgeAnswer {
+        question: 0,
+        correct: true,
+    });
+    assert!(matches!(result, Err(EngineError::QuestionClosed(0))));
+    assert!(engine.game().finale.as_ref().unwrap().answers.is_empty());
+
+    // the open question doesn't block the finale either
+    answer_in_finale(&mut engine, ALICE, true);
+    assert_eq!(engine.game().finale.as_ref().unwrap().answers, [true]);
+}
+
+#[test]
+fn skipped_question_lets_the_finale_go_on() {
+    let mut engine = engine_in_finale(3);
+    let finalist = engine.game().finale.as_ref().unwrap().on_turn();
+    ask(&mut engine, finalist, "Frage ohne Urteil");
+
+    // another question can't be asked while this one is open
+    let result = engine.handle(Command::AskQuestion {
+        player: finalist,
+        question: question("Noch eine Frage"),
+    });
+    assert!(result.is_err());
+
+    let index = engine.open_question().unwrap();
+    let events = engine
+        .handle(Command::SkipQuestion { question: index })
+        .unwrap();
+    assert_eq!(
+        events,
+        vec![Event::AnswerJudged {
+            round: engine.game().round,
+            player: finalist,
+            answer: Answer::Skipped,
+        }]
+    );
+    assert_eq!(engine.open_question(), None);
+    assert!(matches!(
+        engine.handle(Command::SkipQuestion { question: index }),
+        Err(EngineError::QuestionClosed(_))
+    ));
+
+    // the skipped question doesn't count, the same finalist gets a new one
+    let finale = engine.game().finale.as_ref().unwrap();
+    assert!(finale.answers.is_empty());
+    assert_eq!(finale.on_turn(), finalist);
+    answer_in_finale(&mut engine, finalist, true);
+    assert_eq!(engine.game().finale.as_ref().unwrap().answers, [true]);
+}
+
+#[test]
+fn tied_finale_goes_into_sudden_death() {
+    let mut finale = Finale::new([ALICE, BOB], 2);
+
+    finale.answers.extend([true, true, false, false]);
+    assert_eq!(finale.winner(), None);
+
+    finale.answers.extend([false, false]);
+    assert_eq!(finale.winner(), None);
+
+    finale.answers.push(false);
+    assert_eq!(finale.winner(), None);
+    finale.answers.push(true);
+    assert_eq!(finale.winner(), Some(BOB));
+    assert_eq!(finale.score(BOB), (4, 2));
+}
+
+#[test]
+fn finalist_leaving_lets_the_other_one_win() {
+    let mut engine = engine_in_finale(3);
+
+    let events = engine.handle(Command::Leave { player: BOB }).unwrap();
+
+    assert_eq!(
+        events.last(),
+        Some(&Event::GameFinished {
+            winner: Some(ALICE),
+            standings: vec![standing(1, ALICE, 3, None), standing(2, CAROL, 0, Some(1))],
+        })
+    );
+}
+
+fn standing(place: u32, player: UserId, lives: i32, eliminated_in: Option<u32>) -> Standing {
+    Standing {
+        place,
+        player,
+        lives,
+        eliminated_in,
+    }
+}
+
+fn disable_finale(engine: &mut GameEngine) {
+    engine
+        .handle(Command::SetFinaleRules {
+            finale_rules: FinaleRules {
+                enabled: false,
+                ..Default::default()
+            },
+        })
+        .unwrap();
+}
+
+#[test]
+fn standings_follow_the_elimination_order() {
+    let mut engine = engine_with_players(&[(ALICE, 3), (BOB, 1), (CAROL, 1)]);
+    disable_finale(&mut engine);
+    start_voting(&mut engine);
+    vote(&mut engine, ALICE, BOB);
+    engine.handle(Command::EndVoting).unwrap();
+    start_voting(&mut engine);
+    vote(&mut engine, ALICE, CAROL);
+
+    let events = engine.handle(Command::EndVoting).unwrap();
+
+    assert_eq!(
+        events.last(),
+        Some(&Event::GameFinished {
+            winner: Some(ALICE),
+            standings: vec![
+                standing(1, ALICE, 3, None),
+                standing(2, CAROL, 0, Some(2)),
+                standing(3, BOB, 0, Some(1)),
+            ],
+        })
+    );
+    let eliminated = engine
+        .game()
+        .eliminations
+        .iter()
+        .map(|elimination| (elimination.player, elimination.round))
+        .collect::<Vec<_>>();
+    assert_eq!(eliminated, vec![(BOB, 1), (CAROL, 2)]);
+}
+
+#[test]
+fn setting_the_last_lives_to_zero_finishes_the_game() {
+    let mut engine = engine_with_players(&[(ALICE, 3), (BOB, 3)]);
+    engine.handle(Command::StartQuestionRound).unwrap();
+
+    let events = engine
+        .handle(Command::SetLives {
+            player: BOB,
+            lives: 0,
+        })
+        .unwrap();
+
+    assert!(events.contains(&Event::PlayerEliminated { player: BOB }));
+    assert_eq!(
+        events.last(),
+        Some(&Event::GameFinished {
+            winner: Some(ALICE),
+            standings: vec![standing(1, ALICE, 3, None), standing(2, BOB, 0, Some(1))],
+        })
+    );
+    assert!(matches!(
+        engine.handle(Command::CastVote {
+            voter: ALICE,
+            target: BOB,
+        }),
+        Err(EngineError::InvalidPhase(Phase::Finished))
+    ));
+}
+
+#[test]
+fn setting_lives_in_the_lobby_does_not_finish_the_game() {
+    let mut engine = engine_with_players(&[(ALICE, 3), (BOB, 3)]);
+
+    engine
+        .handle(Command::SetLives {
+            player: BOB,
+            lives: 0,
+        })
+        .unwrap();
+
+    assert_eq!(engine.phase(), Phase::Lobby);
+}
+
+#[test]
+fn players_eliminated_together_share_a_place() {
+    let mut engine = engine_with_tie(TieBreak::AllLoseLife, [1, 1, 3, 3]);
+    disable_finale(&mut engine);
+
+    engine.handle(Command::EndVoting).unwrap();
+
+    assert_eq!(
+        engine.game().standings(),
+        vec![
+            standing(1, CAROL, 3, None),
+            standing(1, DAVE, 3, None),
+            standing(3, ALICE, 0, Some(1)),
+            standing(3, BOB, 0, Some(1)),
+        ]
+    );
+}
+
+#[test]
+fn finished_games_are_recorded_once_in_the_stats() {
+    let mut engine = engine_with_players(&[(ALICE, 3), (BOB, 1), (CAROL, 1)]);
+    disable_finale(&mut engine);
+    start_voting(&mut engine);
+    vote(&mut engine, ALICE, BOB);
+    vote(&mut engine, CAROL, BOB);
+    engine.handle(Command::EndVoting).unwrap();
+
+    let mut stats = StatsStore::default();
+    assert!(!stats.record_game(engine.game()));
+
+    start_voting(&mut engine);
+    vote(&mut engine, ALICE, CAROL);
+    engine.handle(Command::EndVoting).unwrap();
+
+    assert!(stats.record_game(engine.game()));
+    assert!(!stats.record_game(engine.game()));
+    assert_eq!(
+        stats.get(ALICE),
+        PlayerStats {
+            games_played: 1,
+            wins: 1,
+            place_sum: 1,
+            best_place: Some(1),
+            votes_received: 0,
+            votes_cast: 2,
+            times_voted_out: 0,
+        }
+    );
+    assert_eq!(
+        stats.get(BOB),
+        PlayerStats {
+            games_played: 1,
+            wins: 0,
+            place_sum: 3,
+            best_place: Some(3),
+            votes_received: 2,
+            votes_cast: 0,
+            times_voted_out: 1,
+        }
+    );
+    let leaderboard = stats
+        .leaderboard()
+        .into_iter()
+        .map(|(player, _)| player)
+        .collect::<Vec<_>>();
+    assert_eq!(leaderboard, vec![ALICE, CAROL, BOB]);
+}
+
+#[test]
+fn twitch_votes_are_only_shown() {
+    let mut engine = engine_with_players(&[(ALICE, 3), (BOB, 3), (CAROL, 3)]);
+    start_voting(&mut engine);
+    vote(&mut engine, ALICE, BOB);
+    for viewer in ["viewer_1", "viewer_2", "viewer_3"] {
+        engine
+            .handle(Command::CastTwitchVote {
+                viewer: viewer.to_owned(),
+                target: CAROL,
+            })
+            .unwrap();
+    }
+    // the last vote of a viewer counts
+    engine
+        .handle(Command::CastTwitchVote {
+            viewer: "viewer_3".to_owned(),
+            target: ALICE,
+        })
+        .unwrap();
+
+    let events = engine.handle(Command::EndVoting).unwrap();
+
+    let twitch_counts = events
+        .iter()
+        .find_map(|event| match event {
+            Event::VotingResolved { twitch_counts, .. } => Some(twitch_counts.clone()),
+            _ => None,
+        })
+        .unwrap();
+    assert_eq!(
+        twitch_counts,
+        MemberVoteCount::from([(CAROL, 2), (ALICE, 1)])
+    );
+    assert_eq!(
+        outcome(&events),
+        &VoteOutcome::ClearWinner {
+            user: BOB,
+            num_votes: 1,
+        }
+    );
+}
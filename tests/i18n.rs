@@ -0,0 +1,118 @@
+use std::collections::BTreeSet;
+
+use ddf_bot::{
+    commands::{language::set_language, question::Verdict},
+    engine::{EngineError, Phase},
+    game::{GhostVote, PlayerError, TieBreak, VoteChangePolicy},
+    i18n::{catalog, localize_commands, localize_error, tr, Locale, Localize},
+    t,
+};
+use poise::{serenity_prelude::UserId, ChoiceParameter};
+
+fn placeholders(message: &str) -> BTreeSet<&str> {
+    message
+        .split('{')
+        .skip(1)
+        .filter_map(|part| part.split_once('}'))
+        .map(|(name, _)| name)
+        .collect()
+}
+
+#[test]
+fn every_locale_has_every_message_with_the_same_placeholders() {
+    let german = catalog(Locale::German);
+
+    for locale in Locale::ALL {
+        let messages = catalog(locale);
+        assert_eq!(
+            messages.keys().collect::<BTreeSet<_>>(),
+            german.keys().collect::<BTreeSet<_>>(),
+            "{locale:?}"
+        );
+
+        for (key, message) in messages {
+            assert_eq!(
+                placeholders(message),
+                placeholders(&german[key]),
+                "{locale:?}: {key}"
+            );
+        }
+    }
+}
+
+fn assert_choices_match<T: ChoiceParameter + Localize>() {
+    for (index, choice) in T::list().into_iter().enumerate() {
+        let value = T::from_index(index).unwrap();
+        assert_eq!(value.localize(Locale::German), choice.name);
+    }
+}
+
+// the German names are how `localize_commands` finds the translations of the choices
+#[test]
+fn choice_names_are_the_german_messages() {
+    assert_choices_match::<TieBreak>();
+    assert_choices_match::<VoteChangePolicy>();
+    assert_choices_match::<GhostVote>();
+    assert_choices_match::<Verdict>();
+}
+
+#[test]
+fn arguments_are_filled_in_and_missing_messages_fall_back() {
+    let player = UserId::new(10);
+
+    assert_eq!(
+        t!(Locale::English, "voting.you_voted", player = player),
+        "✅ You voted for 10."
+    );
+    assert_eq!(
+        t!(Locale::German, "voting.you_voted", player = player),
+        "✅ Du hast 10 gevotet."
+    );
+    assert_eq!(tr(Locale::English, "does.not.exist"), "does.not.exist");
+}
+
+#[test]
+fn errors_are_shown_in_the_language_of_the_guild() {
+    let err = EngineError::InvalidPhase(Phase::Voting);
+    assert_eq!(
+        err.localize(Locale::English),
+        "That's not possible in the current phase (Voting)."
+    );
+    // `Display` stays German for the logs
+    assert_eq!(err.to_string(), err.localize(Locale::German));
+
+    let err = EngineError::from(PlayerError::PlayerNotInGame(UserId::new(10)));
+    let boxed: Box<dyn std::error::Error + Send + Sync> = Box::new(err);
+    assert_eq!(
+        localize_error(boxed.as_ref(), Locale::English),
+        boxed
+            .downcast_ref::<EngineError>()
+            .unwrap()
+            .localize(Locale::English)
+    );
+    assert_ne!(
+        localize_error(boxed.as_ref(), Locale::English),
+        localize_error(boxed.as_ref(), Locale::German)
+    );
+}
+
+#[test]
+fn slash_commands_get_the_translations_of_the_catalog() {
+    let mut commands = vec![set_language()];
+    localize_commands(&mut commands);
+
+    let command = &commands[0];
+    assert_eq!(
+        command.description.as_deref(),
+        Some(tr(Locale::German, "commands.set-language.description"))
+    );
+    assert_eq!(
+        command.description_localizations["en-US"],
+        tr(Locale::English, "commands.set-language.description")
+    );
+
+    let parameter = &command.parameters[0];
+    assert_eq!(parameter.name, "sprache");
+    assert_eq!(parameter.name_localizations["en-GB"], "language");
+    assert_eq!(parameter.name_localizations["de"], "sprache");
+}
@@ -0,0 +1,131 @@
+use ddf_bot::{
+    engine::{Command, GameEngine, Phase},
+    i18n::Locale,
+    overlay::{serve, Overlay, OverlayState, OverlayStates},
+};
+use poise::serenity_prelude::{futures::StreamExt, ChannelId, GuildId, UserId};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+
+const MODERATOR: UserId = UserId::new(1);
+const ALICE: UserId = UserId::new(10);
+const BOB: UserId = UserId::new(11);
+const CAROL: UserId = UserId::new(12);
+const DAVE: UserId = UserId::new(13);
+
+fn engine_in_voting(anonymous: bool) -> GameEngine {
+    let mut engine = GameEngine::new(MODERATOR, MODERATOR, ChannelId::new(100));
+    for player in [ALICE, BOB, CAROL, DAVE] {
+        engine.handle(Command::Join { player, lives: 3 }).unwrap();
+    }
+    engine
+        .handle(Command::SetLives {
+            player: DAVE,
+            lives: 0,
+        })
+        .unwrap();
+    engine
+        .handle(Command::StartVoting {
+            creator: MODERATOR,
+            deadline: None,
+            anonymous,
+        })
+        .unwrap();
+    engine
+        .handle(Command::CastVote {
+            voter: ALICE,
+            target: BOB,
+        })
+        .unwrap();
+
+    engine
+}
+
+async fn next_state(
+    socket: &mut (impl StreamExt<Item = Result<Message, tokio_tungstenite::tungstenite::Error>> + Unpin),
+) -> serde_json::Value {
+    let message = socket.next().await.unwrap().unwrap();
+    serde_json::from_str(message.to_text().unwrap()).unwrap()
+}
+
+#[test]
+fn state_shows_lives_eliminations_and_the_tally() {
+    let state = OverlayState::new(&engine_in_voting(false), Locale::German);
+
+    assert_eq!(state.phase, Phase::Voting);
+    let players = state
+        .players
+        .iter()
+        .map(|player| (player.id, player.lives, player.eliminated, player.voted))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        players,
+        [
+            (ALICE, 3, false, true),
+            (BOB, 3, false, false),
+            (CAROL, 3, false, false),
+            (DAVE, 0, true, false),
+        ]
+    );
+
+    let counts = state.voting.unwrap().counts.unwrap();
+    assert_eq!(counts.get(&BOB), Some(&1));
+}
+
+#[test]
+fn anonymous_votings_hide_the_tally() {
+    let state = OverlayState::new(&engine_in_voting(true), Locale::German);
+
+    let voting = state.voting.unwrap();
+    assert_eq!(voting.counts, None);
+    // who already voted isn't secret in an anonymous voting either
+    assert!(state.players.iter().any(|player| player.voted));
+}
+
+#[tokio::test]
+async fn server_serves_the_page_and_pushes_every_change() {
+    let overlay = Overlay::default();
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(serve(listener, overlay.subscribe()));
+
+    let mut http = TcpStream::connect(address).await.unwrap();
+    http.write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n")
+        .await
+        .unwrap();
+    let mut page = String::new();
+    http.read_to_string(&mut page).await.unwrap();
+    assert!(page.starts_with("HTTP/1.1 200 OK"));
+    assert!(page.contains("<html"));
+
+    let (mut socket, _) = connect_async(format!("ws://{address}/ws?guild=1"))
+        .await
+        .unwrap();
+    assert_eq!(next_state(&mut socket).await, serde_json::Value::Null);
+
+    // without a Discord connection the IDs stand in for the names
+    let engine = engine_in_voting(false);
+    overlay
+        .publish(OverlayStates::from([
+            (GuildId::new(1), OverlayState::new(&engine, Locale::English)),
+            (
+                GuildId::new(2),
+                OverlayState::new(
+                    &GameEngine::new(MODERATOR, MODERATOR, ChannelId::new(200)),
+                    Locale::German,
+                ),
+            ),
+        ]))
+        .await;
+
+    let state = next_state(&mut socket).await;
+    assert_eq!(state["phase"], "Voting");
+    assert_eq!(state["players"][0]["name"], ALICE.to_string());
+    assert_eq!(state["players"][3]["eliminated"], true);
+    assert_eq!(state["voting"]["counts"][BOB.to_string()], 1);
+    assert_eq!(state["labels"]["phase"], "Voting");
+    assert_eq!(state["labels"]["round"], "Round 1");
+}
@@ -0,0 +1,56 @@
+use std::path::PathBuf;
+
+use ddf_bot::{
+    i18n::Locale,
+    persistence::{PersistenceError, SessionSnapshot, Snapshot, SnapshotStore},
+};
+use poise::serenity_prelude::GuildId;
+
+fn snapshot_path(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("ddf_bot_{}_{name}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir.join("ddf_snapshot.json")
+}
+
+fn snapshot_of(guild: u64) -> Snapshot {
+    Snapshot {
+        sessions: [(
+            GuildId::new(guild),
+            SessionSnapshot {
+                locale: Locale::English,
+                ..SessionSnapshot::default()
+            },
+        )]
+        .into(),
+    }
+}
+
+#[tokio::test]
+async fn saves_at_the_same_time_leave_a_readable_snapshot() {
+    let store = SnapshotStore::new(snapshot_path("concurrent"));
+
+    let saves = (1..=20).map(|guild| store.save(async move { snapshot_of(guild) }));
+    for result in poise::serenity_prelude::futures::future::join_all(saves).await {
+        result.unwrap();
+    }
+
+    let snapshot = store.load().unwrap().unwrap();
+    assert_eq!(snapshot.sessions.len(), 1);
+    std::fs::remove_dir_all(store.path().parent().unwrap()).unwrap();
+}
+
+#[test]
+fn corrupt_snapshot_can_be_set_aside() {
+    let store = SnapshotStore::new(snapshot_path("corrupt"));
+    std::fs::write(store.path(), "{\"sessions\": ").unwrap();
+
+    assert!(matches!(store.load(), Err(PersistenceError::Json(_))));
+
+    let moved_to = store.set_aside().unwrap();
+    assert_eq!(
+        std::fs::read_to_string(&moved_to).unwrap(),
+        "{\"sessions\": "
+    );
+    assert!(store.load().unwrap().is_none());
+    std::fs::remove_dir_all(store.path().parent().unwrap()).unwrap();
+}
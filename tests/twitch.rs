@@ -0,0 +1,103 @@
+use ddf_bot::twitch::{match_player, parse_vote, ChatMessage, TwitchChat, TwitchConfig};
+use poise::serenity_prelude::{GuildId, UserId};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+};
+
+const ALICE: UserId = UserId::new(10);
+const BOB: UserId = UserId::new(11);
+
+fn config() -> TwitchConfig {
+    TwitchConfig {
+        server: "127.0.0.1".to_owned(),
+        port: 0,
+        tls: false,
+        nick: "ddf_bot".to_owned(),
+        token: Some("secret".to_owned()),
+        channel: "libafo".to_owned(),
+        guild_id: GuildId::new(1),
+    }
+}
+
+fn players() -> Vec<(UserId, Vec<String>)> {
+    vec![
+        (ALICE, vec!["alice_01".to_owned(), "Alice".to_owned()]),
+        (BOB, vec!["bobby".to_owned()]),
+    ]
+}
+
+#[tokio::test]
+async fn chat_logs_in_answers_pings_and_reads_messages() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+
+    let server = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let (reader, mut writer) = stream.into_split();
+        let mut lines = BufReader::new(reader).lines();
+
+        let mut login = Vec::new();
+        for _ in 0..3 {
+            login.push(lines.next_line().await.unwrap().unwrap());
+        }
+
+        writer
+            .write_all(
+                b":tmi.twitch.tv 001 ddf_bot :Welcome, GLHF!\r\n\
+                  PING :tmi.twitch.tv\r\n\
+                  @badges=;color= :viewer!viewer@viewer.tmi.twitch.tv PRIVMSG #libafo :!vote @Alice\r\n",
+            )
+            .await
+            .unwrap();
+        let pong = lines.next_line().await.unwrap().unwrap();
+
+        (login, pong)
+    });
+
+    let stream = TcpStream::connect(address).await.unwrap();
+    let mut chat = TwitchChat::login(stream, &config()).await.unwrap();
+
+    assert_eq!(
+        chat.next_message().await.unwrap(),
+        Some(ChatMessage {
+            user: "viewer".to_owned(),
+            text: "!vote @Alice".to_owned(),
+        })
+    );
+    assert_eq!(chat.next_message().await.unwrap(), None);
+
+    let (login, pong) = server.await.unwrap();
+    assert_eq!(
+        login,
+        vec!["PASS oauth:secret", "NICK ddf_bot", "JOIN #libafo"]
+    );
+    assert_eq!(pong, "PONG :tmi.twitch.tv");
+}
+
+#[test]
+fn only_vote_commands_are_votes() {
+    assert_eq!(parse_vote("!vote @Alice"), Some("Alice"));
+    assert_eq!(parse_vote("  !VOTE bobby "), Some("bobby"));
+    assert_eq!(parse_vote("!vote"), None);
+    assert_eq!(parse_vote("!voter Alice"), None);
+    assert_eq!(parse_vote("ich vote alice"), None);
+}
+
+#[test]
+fn chat_names_are_matched_to_players() {
+    assert_eq!(match_player("ALICE", &players()), Some(ALICE));
+    assert_eq!(match_player("alice_1", &players()), Some(ALICE));
+    assert_eq!(match_player("boby", &players()), Some(BOB));
+    assert_eq!(match_player("carol", &players()), None);
+}
+
+#[test]
+fn ambiguous_names_are_not_matched() {
+    let players = vec![
+        (ALICE, vec!["anna".to_owned()]),
+        (BOB, vec!["Anna".to_owned()]),
+    ];
+
+    assert_eq!(match_player("anna", &players), None);
+}